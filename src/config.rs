@@ -0,0 +1,454 @@
+use sep_40_oracle::{Asset, PriceFeedClient};
+use soroban_sdk::{panic_with_error, Address, Env, Symbol, Vec};
+
+use crate::{
+    errors::OracleAggregatorErrors,
+    fixed_point::scale,
+    storage,
+    types::{
+        AssetConfig, AssetSource, DerivedAsset, OracleConfig, PathLeg, PegFeed, PeggedAsset,
+        PriceBounds, UsdConversion, UsdConversionConfig, UsdcSource,
+    },
+};
+
+/// Validate the max age of a price
+///
+/// ### Errors
+/// * `InvalidMaxAge` - The max age is not between 360 (6m) and 3600 (60m)
+pub fn validate_max_age(e: &Env, max_age: u64) {
+    if !(360..=3600).contains(&max_age) {
+        panic_with_error!(e, OracleAggregatorErrors::InvalidMaxAge);
+    }
+}
+
+/// Validate the price bounds of an asset
+///
+/// ### Errors
+/// * `InvalidPriceBounds` - The minimum price is not positive, or is greater than the maximum price
+pub fn validate_price_bounds(e: &Env, bounds: &PriceBounds) {
+    if bounds.min <= 0 || bounds.min > bounds.max {
+        panic_with_error!(e, OracleAggregatorErrors::InvalidPriceBounds);
+    }
+}
+
+/// Load the configs of the source oracles from the oracles themselves
+///
+/// ### Errors
+/// * `InvalidOracles` - No source oracles were provided
+/// * `InvalidBaseAsset` - The base asset of a source oracle is not USDC, or USD with a USD
+///   conversion set
+pub fn load_oracle_configs(
+    e: &Env,
+    oracle_ids: &Vec<Address>,
+    usdc: &Address,
+) -> Vec<OracleConfig> {
+    if oracle_ids.is_empty() {
+        panic_with_error!(e, OracleAggregatorErrors::InvalidOracles);
+    }
+    let mut configs: Vec<OracleConfig> = Vec::new(e);
+    for oracle_id in oracle_ids.iter() {
+        let oracle = PriceFeedClient::new(e, &oracle_id);
+        let base = oracle.base();
+        // the base asset can be a symbol aliased to USDC
+        match resolve_asset(e, &base) {
+            Asset::Stellar(addr) => {
+                if addr != *usdc {
+                    panic_with_error!(e, OracleAggregatorErrors::InvalidBaseAsset);
+                    // otherwise, set the base to the address of the base asset
+                }
+            }
+            // prices in USD are converted to USDC with the USD conversion
+            base if is_usd(e, &base) => {
+                if storage::get_usd_conversion(e).is_none() {
+                    panic_with_error!(e, OracleAggregatorErrors::InvalidBaseAsset);
+                }
+            }
+            _ => panic_with_error!(e, OracleAggregatorErrors::InvalidBaseAsset),
+        }
+        configs.push_back(OracleConfig {
+            oracle_id,
+            resolution: oracle.resolution(),
+            decimals: oracle.decimals(),
+            base,
+        });
+    }
+    configs
+}
+
+/// Load the config of a USDC source from the oracle itself
+///
+/// ### Errors
+/// * `InvalidBaseAsset` - The oracle does not report prices in USD
+pub fn load_usdc_source(e: &Env, source: &AssetSource) -> UsdcSource {
+    let oracle = PriceFeedClient::new(e, &source.oracle_id);
+    let base = oracle.base();
+    if !is_usd(e, &base) {
+        panic_with_error!(e, OracleAggregatorErrors::InvalidBaseAsset);
+    }
+    UsdcSource {
+        oracle: OracleConfig {
+            oracle_id: source.oracle_id.clone(),
+            resolution: oracle.resolution(),
+            decimals: oracle.decimals(),
+            base,
+        },
+        asset: source.asset.clone(),
+    }
+}
+
+/// Load the config of a USD conversion, loading the config of its oracle from the oracle itself
+///
+/// ### Errors
+/// * `InvalidBaseAsset` - The oracle does not report prices in USD
+pub fn load_usd_conversion(e: &Env, conversion: &UsdConversion) -> UsdConversionConfig {
+    match conversion {
+        UsdConversion::Fixed => UsdConversionConfig::Fixed,
+        UsdConversion::Source(source) => UsdConversionConfig::Source(load_usdc_source(e, source)),
+    }
+}
+
+/// Check if an asset is USD, the base asset of oracles that report prices in USD
+pub fn is_usd(e: &Env, asset: &Asset) -> bool {
+    match asset {
+        Asset::Other(symbol) => *symbol == Symbol::new(e, "USD"),
+        Asset::Stellar(_) => false,
+    }
+}
+
+/// Get the resolution of the aggregator, which is the coarsest resolution of the source oracles
+pub fn get_resolution(e: &Env) -> u32 {
+    let mut resolution = 0;
+    for config in storage::get_oracle_configs(e).iter() {
+        resolution = resolution.max(config.resolution);
+    }
+    resolution
+}
+
+/// Validate an asset config against the source oracle configs
+///
+/// ### Errors
+/// * `InvalidAssetOracle` - The asset config has no sources, or uses an oracle that is not a source oracle
+/// * `InvalidMaxAge` - The max age is not between 360 (6m) and 3600 (60m)
+pub fn validate_asset_config(e: &Env, configs: &Vec<OracleConfig>, asset_config: &AssetConfig) {
+    if asset_config.sources.is_empty() {
+        panic_with_error!(e, OracleAggregatorErrors::InvalidAssetOracle);
+    }
+    for source in asset_config
+        .sources
+        .iter()
+        .chain(asset_config.fallbacks.iter())
+    {
+        if !configs
+            .iter()
+            .any(|config| config.oracle_id == source.oracle_id)
+        {
+            panic_with_error!(e, OracleAggregatorErrors::InvalidAssetOracle);
+        }
+    }
+    if let Some(max_age) = asset_config.max_age {
+        validate_max_age(e, max_age);
+    }
+}
+
+/// Validate and store the asset config for an asset, and add the asset to the configured assets
+///
+/// ### Errors
+/// * `InvalidAssetOracle` - The asset config has no sources, or uses an oracle that is not a source oracle
+/// * `InvalidMaxAge` - The max age is not between 360 (6m) and 3600 (60m)
+/// * `InvalidDerivedAsset` - The asset is priced from an underlying asset
+/// * `InvalidPeggedAsset` - The asset is a pegged asset
+/// * `InvalidAssetPath` - The asset is priced through a path of oracles
+pub fn set_asset_config(
+    e: &Env,
+    configs: &Vec<OracleConfig>,
+    asset: &Asset,
+    asset_config: &AssetConfig,
+) {
+    validate_asset_config(e, configs, asset_config);
+    if storage::get_derived_asset(e, asset).is_some() {
+        panic_with_error!(e, OracleAggregatorErrors::InvalidDerivedAsset);
+    }
+    if storage::get_pegged_assets(e).contains_key(asset.clone()) {
+        panic_with_error!(e, OracleAggregatorErrors::InvalidPeggedAsset);
+    }
+    if storage::get_asset_path(e, asset).is_some() {
+        panic_with_error!(e, OracleAggregatorErrors::InvalidAssetPath);
+    }
+    storage::set_asset_config(e, asset, asset_config);
+    let mut assets = storage::get_assets(e);
+    if !assets.iter().any(|existing| assets_equal(&existing, asset)) {
+        assets.push_back(asset.clone());
+        storage::set_assets(e, &assets);
+    }
+}
+
+/// Validate and store the underlying asset a derived asset is priced from, and add or remove the
+/// asset from the derived assets
+///
+/// ### Errors
+/// * `InvalidDerivedAsset` - The asset is USDC, a pegged asset, priced through a path or has an
+///   asset config, the underlying asset does not have an asset config, or the ratio is zero
+pub fn set_derived_asset(e: &Env, asset: &Asset, derived: &Option<DerivedAsset>) {
+    let mut derived_assets = storage::get_derived_assets(e);
+    let index = derived_assets
+        .iter()
+        .position(|existing| assets_equal(&existing, asset));
+    match derived {
+        Some(derived) => {
+            let usdc = Asset::Stellar(storage::get_usdc(e));
+            if assets_equal(asset, &usdc)
+                || storage::get_pegged_assets(e).contains_key(asset.clone())
+                || storage::get_asset_path(e, asset).is_some()
+                || storage::get_asset_config(e, asset).is_some()
+                || storage::get_asset_config(e, &derived.underlying).is_none()
+                || derived.ratio == 0
+            {
+                panic_with_error!(e, OracleAggregatorErrors::InvalidDerivedAsset);
+            }
+            if index.is_none() {
+                derived_assets.push_back(asset.clone());
+            }
+        }
+        None => {
+            if let Some(index) = index {
+                derived_assets.remove(index as u32);
+                remove_stored_prices(e, asset);
+            }
+        }
+    }
+    storage::set_derived_asset(e, asset, derived);
+    storage::set_derived_assets(e, &derived_assets);
+}
+
+/// Validate and register a pegged asset, or remove it from the registry
+///
+/// ### Errors
+/// * `InvalidPeggedAsset` - The asset is USDC, a derived asset, priced through a path or has an
+///   asset config, or the fixed price is not positive
+/// * `InvalidAssetOracle` - The feed is not a source oracle
+pub fn set_pegged_asset(
+    e: &Env,
+    configs: &Vec<OracleConfig>,
+    asset: &Asset,
+    pegged: &Option<PeggedAsset>,
+) {
+    let mut pegged_assets = storage::get_pegged_assets(e);
+    match pegged {
+        Some(pegged) => {
+            let usdc = Asset::Stellar(storage::get_usdc(e));
+            if assets_equal(asset, &usdc)
+                || storage::get_asset_config(e, asset).is_some()
+                || storage::get_derived_asset(e, asset).is_some()
+                || storage::get_asset_path(e, asset).is_some()
+                || pegged.price <= 0
+            {
+                panic_with_error!(e, OracleAggregatorErrors::InvalidPeggedAsset);
+            }
+            if let PegFeed::Source(feed) = &pegged.feed {
+                validate_pegged_feed(e, configs, feed);
+            }
+            pegged_assets.set(asset.clone(), pegged.clone());
+        }
+        None => {
+            if pegged_assets.remove(asset.clone()).is_some() {
+                remove_stored_prices(e, asset);
+            }
+        }
+    }
+    storage::set_pegged_assets(e, &pegged_assets);
+}
+
+/// Validate that the feed of a pegged asset is a source oracle
+///
+/// ### Errors
+/// * `InvalidAssetOracle` - The feed is not a source oracle
+pub fn validate_pegged_feed(e: &Env, configs: &Vec<OracleConfig>, feed: &AssetSource) {
+    if !configs
+        .iter()
+        .any(|config| config.oracle_id == feed.oracle_id)
+    {
+        panic_with_error!(e, OracleAggregatorErrors::InvalidAssetOracle);
+    }
+}
+
+/// Load the legs of a path of oracles from the oracles themselves
+///
+/// ### Errors
+/// * `InvalidAssetPath` - The path is empty, a leg is not quoted in the asset of the next leg, or
+///   the base asset of the oracle of the last leg is not USDC
+pub fn load_asset_path(e: &Env, path: &Vec<AssetSource>) -> Vec<PathLeg> {
+    let mut legs: Vec<PathLeg> = Vec::new(e);
+    for source in path.iter() {
+        // each leg must be quoted in the asset reported by the next leg
+        if let Some(previous_leg) = legs.last() {
+            let quote = resolve_asset(e, &previous_leg.oracle.base);
+            if !assets_equal(&quote, &resolve_asset(e, &source.asset)) {
+                panic_with_error!(e, OracleAggregatorErrors::InvalidAssetPath);
+            }
+        }
+        let oracle = PriceFeedClient::new(e, &source.oracle_id);
+        legs.push_back(PathLeg {
+            oracle: OracleConfig {
+                oracle_id: source.oracle_id.clone(),
+                resolution: oracle.resolution(),
+                decimals: oracle.decimals(),
+                base: oracle.base(),
+            },
+            asset: source.asset.clone(),
+        });
+    }
+    // the product of the legs must be a price in USDC
+    let usdc = Asset::Stellar(storage::get_usdc(e));
+    match legs.last() {
+        Some(leg) if assets_equal(&resolve_asset(e, &leg.oracle.base), &usdc) => legs,
+        _ => panic_with_error!(e, OracleAggregatorErrors::InvalidAssetPath),
+    }
+}
+
+/// Store the path of oracles an asset is priced through, and add or remove the asset from the
+/// path assets
+///
+/// ### Errors
+/// * `InvalidAssetPath` - The asset is USDC, a derived or pegged asset, or has an asset config
+pub fn set_asset_path(e: &Env, asset: &Asset, path: &Option<Vec<PathLeg>>) {
+    let mut path_assets = storage::get_path_assets(e);
+    let index = path_assets
+        .iter()
+        .position(|existing| assets_equal(&existing, asset));
+    match path {
+        Some(_) => {
+            let usdc = Asset::Stellar(storage::get_usdc(e));
+            if assets_equal(asset, &usdc)
+                || storage::get_asset_config(e, asset).is_some()
+                || storage::get_derived_asset(e, asset).is_some()
+                || storage::get_pegged_assets(e).contains_key(asset.clone())
+            {
+                panic_with_error!(e, OracleAggregatorErrors::InvalidAssetPath);
+            }
+            if index.is_none() {
+                path_assets.push_back(asset.clone());
+            }
+        }
+        None => {
+            if let Some(index) = index {
+                path_assets.remove(index as u32);
+                remove_stored_prices(e, asset);
+            }
+        }
+    }
+    storage::set_asset_path(e, asset, path);
+    storage::set_path_assets(e, &path_assets);
+}
+
+/// Get the assets with an asset config, the derived, pegged and path assets, and USDC
+///
+/// USDC is only included once, even if it has an asset config.
+pub fn get_listed_assets(e: &Env) -> Vec<Asset> {
+    let mut assets = storage::get_assets(e);
+    assets.append(&storage::get_derived_assets(e));
+    assets.append(&storage::get_pegged_assets(e).keys());
+    assets.append(&storage::get_path_assets(e));
+    let usdc = Asset::Stellar(storage::get_usdc(e));
+    if !assets.iter().any(|existing| assets_equal(&existing, &usdc)) {
+        assets.push_back(usdc);
+    }
+    assets
+}
+
+/// Check if an asset is listed, either with an asset config, as a derived, pegged or path asset,
+/// or as USDC
+pub fn is_listed(e: &Env, asset: &Asset) -> bool {
+    get_listed_assets(e)
+        .iter()
+        .any(|existing| assets_equal(&existing, asset))
+}
+
+/// Remove the price bounds and last known good price of an asset that is no longer listed
+fn remove_stored_prices(e: &Env, asset: &Asset) {
+    storage::set_price_bounds(e, asset, &None);
+    storage::remove_last_price(e, asset);
+}
+
+/// Rescale every price stored in the aggregator's decimals from `from_decimals` to `to_decimals`
+///
+/// This includes the fixed price of each pegged asset, and the price bounds and last known good
+/// price of each listed asset. Prices that lose precision are rounded with the configured rounding.
+///
+/// ### Errors
+/// * `ScalingOverflow` - A rescaled price does not fit in an i128
+/// * `InvalidPeggedAsset` - The fixed price of a pegged asset is not positive once rescaled
+pub fn rescale_stored_prices(e: &Env, from_decimals: u32, to_decimals: u32) {
+    if from_decimals == to_decimals {
+        return;
+    }
+    let rounding = storage::get_rounding(e);
+    let rescale = |value: i128| {
+        scale(value, from_decimals, to_decimals, rounding)
+            .unwrap_or_else(|error| panic_with_error!(e, error))
+    };
+
+    let mut pegged_assets = storage::get_pegged_assets(e);
+    for (asset, mut pegged) in pegged_assets.iter() {
+        pegged.price = rescale(pegged.price);
+        if pegged.price <= 0 {
+            panic_with_error!(e, OracleAggregatorErrors::InvalidPeggedAsset);
+        }
+        pegged_assets.set(asset, pegged);
+    }
+
+    for asset in get_listed_assets(e).iter() {
+        if let Some(bounds) = storage::get_price_bounds(e, &asset) {
+            let bounds = PriceBounds {
+                min: rescale(bounds.min),
+                max: rescale(bounds.max),
+            };
+            storage::set_price_bounds(e, &asset, &Some(bounds));
+        }
+        if let Some(mut price) = storage::get_last_price(e, &asset) {
+            price.price = rescale(price.price);
+            storage::set_last_price(e, &asset, &price);
+        }
+    }
+    storage::set_pegged_assets(e, &pegged_assets);
+}
+
+/// Check if an asset is paused, either individually or because every asset is paused
+///
+/// A derived asset is also paused while its underlying asset is paused.
+pub fn is_paused(e: &Env, asset: &Asset) -> bool {
+    if storage::get_paused(e) {
+        return true;
+    }
+    let paused_assets = storage::get_paused_assets(e);
+    let is_asset_paused = |asset: &Asset| {
+        paused_assets
+            .iter()
+            .any(|paused| assets_equal(&paused, asset))
+    };
+    is_asset_paused(asset)
+        || storage::get_derived_asset(e, asset)
+            .is_some_and(|derived| is_asset_paused(&derived.underlying))
+}
+
+/// Resolve an asset to the asset used to key its configs
+///
+/// An `Asset::Other` symbol with an alias resolves to the Stellar asset it is aliased to. Every
+/// other asset resolves to itself.
+pub fn resolve_asset(e: &Env, asset: &Asset) -> Asset {
+    match asset {
+        Asset::Other(symbol) => match storage::get_alias_address(e, symbol) {
+            Some(address) => Asset::Stellar(address),
+            None => asset.clone(),
+        },
+        Asset::Stellar(_) => asset.clone(),
+    }
+}
+
+/// Check if two assets are the same
+pub fn assets_equal(a: &Asset, b: &Asset) -> bool {
+    match (a, b) {
+        (Asset::Stellar(a), Asset::Stellar(b)) => a == b,
+        (Asset::Other(a), Asset::Other(b)) => a == b,
+        _ => false,
+    }
+}
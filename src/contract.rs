@@ -1,6 +1,25 @@
-use crate::{errors::OracleAggregatorErrors, price_data::get_price, storage, types::OracleConfig};
-use sep_40_oracle::{Asset, PriceData, PriceFeedClient, PriceFeedTrait};
-use soroban_sdk::{contract, contractimpl, panic_with_error, Address, Env, Vec};
+use crate::{
+    config::{self, assets_equal},
+    errors::OracleAggregatorErrors,
+    events::OracleAggregatorEvents,
+    price_data::{
+        cross_price, get_asset_max_age, get_grace_price, get_last_prices, get_price, get_price_at,
+        get_price_detail, get_prices, get_rounded_price, get_sourced_price,
+        get_usdc_denominated_price, get_usdc_price_detail, RoundReader,
+    },
+    storage, twap,
+    types::{
+        AssetConfig, AssetSource, DerivedAsset, GracePrice, OracleConfig, PathLeg, PegFeed,
+        PeggedAsset, PriceBounds, PriceDetail, RetryStrategy, RoundingMode, SourcedPrice,
+        UsdConversion, UsdConversionConfig, UsdcSource, Version,
+    },
+    upgrade,
+    usdc::{fixed_usdc_price, get_usdc_price, get_usdc_price_at, get_usdc_prices},
+};
+use sep_40_oracle::{Asset, PriceData, PriceFeedTrait};
+use soroban_sdk::{
+    contract, contractimpl, panic_with_error, Address, BytesN, Env, String, Symbol, Vec,
+};
 
 #[contract]
 pub struct OracleAggregator;
@@ -8,19 +27,43 @@ pub struct OracleAggregator;
 #[contractimpl]
 impl PriceFeedTrait for OracleAggregator {
     fn resolution(e: Env) -> u32 {
-        panic_with_error!(e, OracleAggregatorErrors::NotImplemented);
+        config::get_resolution(&e)
     }
 
-    fn price(e: Env, _asset: Asset, _timestamp: u64) -> Option<PriceData> {
-        panic_with_error!(e, OracleAggregatorErrors::NotImplemented);
+    fn price(e: Env, asset: Asset, timestamp: u64) -> Option<PriceData> {
+        upgrade::require_current_schema(&e);
+        let asset = config::resolve_asset(&e, &asset);
+        if config::is_paused(&e, &asset) {
+            return None;
+        }
+        if is_usdc(&e, &asset) {
+            // if the asset is USDC, return the price in base asset
+            return get_usdc_price_at(&e, &mut RoundReader::new(&e), timestamp);
+        }
+
+        get_price_at(&e, &asset, timestamp)
     }
 
-    fn prices(e: Env, _asset: Asset, _records: u32) -> Option<Vec<PriceData>> {
-        panic_with_error!(e, OracleAggregatorErrors::NotImplemented);
+    fn prices(e: Env, asset: Asset, records: u32) -> Option<Vec<PriceData>> {
+        upgrade::require_current_schema(&e);
+        let asset = config::resolve_asset(&e, &asset);
+        if config::is_paused(&e, &asset) {
+            return None;
+        }
+        if is_usdc(&e, &asset) {
+            // if the asset is USDC, return a price in base asset for each round
+            return get_usdc_prices(&e, records, config::get_resolution(&e));
+        }
+
+        get_prices(&e, &asset, records)
     }
 
     fn base(e: Env) -> Asset {
-        storage::get_oracle_config(&e).base
+        match storage::get_usdc_source(&e) {
+            // prices are converted to the base asset of the USDC source
+            Some(source) => source.oracle.base,
+            None => Asset::Stellar(storage::get_usdc(&e)),
+        }
     }
 
     fn decimals(e: Env) -> u32 {
@@ -28,99 +71,889 @@ impl PriceFeedTrait for OracleAggregator {
     }
 
     fn assets(e: Env) -> Vec<Asset> {
-        let config = storage::get_oracle_config(&e);
-        let usdc = storage::get_usdc(&e);
-        let oracle = PriceFeedClient::new(&e, &config.oracle_id);
-        let mut assets = oracle.assets();
-        for asset in &assets {
-            match asset {
-                Asset::Stellar(addr) if addr == usdc => {
-                    // if the asset is USDC, return the price in base asset
-                    return assets;
-                }
-                _ => {}
+        // return the listed assets that are not paused
+        let mut active_assets = Vec::new(&e);
+        for asset in config::get_listed_assets(&e).iter() {
+            if !config::is_paused(&e, &asset) {
+                active_assets.push_back(asset);
             }
         }
-        assets.push_back(Asset::Stellar(usdc));
-        assets
+        active_assets
     }
 
     fn lastprice(e: Env, asset: Asset) -> Option<PriceData> {
-        let usdc = storage::get_usdc(&e);
-        match asset {
-            Asset::Stellar(addr) if addr == usdc => {
-                // if the asset is USDC, return the price in base asset
-                return Some(PriceData {
-                    price: 10i128.pow(storage::get_decimals(&e) as u32),
-                    timestamp: e.ledger().timestamp(),
-                });
-            }
-            _ => {}
+        upgrade::require_current_schema(&e);
+        let asset = config::resolve_asset(&e, &asset);
+        if config::is_paused(&e, &asset) {
+            return None;
+        }
+        if is_usdc(&e, &asset) {
+            // if the asset is USDC, return the price in base asset
+            return get_usdc_price(&e, storage::get_rounding(&e));
         }
 
         get_price(&e, &asset)
     }
 }
 
+/// Check if the asset is the configured USDC asset
+fn is_usdc(e: &Env, asset: &Asset) -> bool {
+    match asset {
+        Asset::Stellar(addr) => *addr == storage::get_usdc(e),
+        _ => false,
+    }
+}
+
+/// Fetch the most recent price of an asset in USDC, before any conversion to USD
+fn lastprice_in_usdc(e: &Env, asset: &Asset) -> Option<PriceData> {
+    if is_usdc(e, asset) {
+        return Some(fixed_usdc_price(e, e.ledger().timestamp()));
+    }
+
+    get_usdc_denominated_price(e, asset)
+}
+
 #[contractimpl]
 impl OracleAggregator {
     /// Initialize the oracle aggregator contract.
     ///
     /// ### Arguments
-    /// * `oracle_id` - The address of the oracle
+    /// * `admin` - The address of the admin
+    /// * `oracle_ids` - The addresses of the source oracles
     /// * `usdc_id` - The address of the USDC asset
     /// * `decimals` - The decimals the oracle will report in
     /// * `max_age` - The maximum time the oracle will look back for a price (in seconds)
+    /// * `assets` - The assets the oracle will report prices for, and their asset configs
+    /// * `usd_conversion` - How prices reported in USD by source oracles are converted to USDC, if
+    ///   any source oracle reports prices in USD
     ///
     /// ### Errors
     /// * `InvalidMaxAge` - The max age is not between 360 (6m) and 3600 (60m)
-    /// * `InvalidBaseAsset` - The base asset of the oracle is not USDC
+    /// * `InvalidBaseAsset` - The base asset of a source oracle is not USDC, or is USD without a USD conversion
+    /// * `InvalidOracles` - No source oracles were provided
+    /// * `InvalidAssetOracle` - An asset config has no sources, or uses an oracle that is not a source oracle
+    #[allow(clippy::too_many_arguments)]
     pub fn __constructor(
         e: Env,
-        oracle_id: Address,
+        admin: Address,
+        oracle_ids: Vec<Address>,
         usdc_id: Address,
         decimals: u32,
         max_age: u64,
+        assets: Vec<(Asset, AssetConfig)>,
+        usd_conversion: Option<UsdConversion>,
     ) {
         storage::extend_instance(&e);
+        storage::set_schema_version(&e, &upgrade::SCHEMA_VERSION);
+        storage::set_admin(&e, &admin);
         storage::set_decimals(&e, &decimals);
-        if max_age < 360 || max_age > 3600 {
-            panic_with_error!(&e, OracleAggregatorErrors::InvalidMaxAge);
-        }
+        config::validate_max_age(&e, max_age);
         storage::set_max_age(&e, &max_age);
 
-        let oracle = PriceFeedClient::new(&e, &oracle_id);
-        let base = oracle.base();
-        match base.clone() {
-            Asset::Stellar(addr) => {
-                if addr != usdc_id {
-                    panic_with_error!(&e, OracleAggregatorErrors::InvalidBaseAsset);
-                    // otherwise, set the base to the address of the base asset
-                }
-            }
+        // the USD conversion is required to accept source oracles that report prices in USD
+        let usd_conversion_config = usd_conversion
+            .as_ref()
+            .map(|conversion| config::load_usd_conversion(&e, conversion));
+        storage::set_usd_conversion(&e, &usd_conversion_config);
+        let configs = config::load_oracle_configs(&e, &oracle_ids, &usdc_id);
+        storage::set_usdc(&e, &usdc_id);
+        storage::set_oracle_configs(&e, &configs);
 
-            _ => panic_with_error!(&e, OracleAggregatorErrors::InvalidBaseAsset),
+        for (asset, asset_config) in assets.iter() {
+            config::set_asset_config(&e, &configs, &asset, &asset_config);
         }
 
-        storage::set_usdc(&e, &usdc_id);
-        storage::set_oracle_config(
+        OracleAggregatorEvents::init(
             &e,
-            &OracleConfig {
-                oracle_id,
-                resolution: oracle.resolution(),
-                decimals: oracle.decimals(),
-                base,
-            },
+            admin,
+            oracle_ids,
+            usdc_id,
+            decimals,
+            max_age,
+            usd_conversion,
         );
     }
 
+    /// (Admin only) Set a new admin
+    ///
+    /// ### Arguments
+    /// * `new_admin` - The address of the new admin
+    pub fn set_admin(e: Env, new_admin: Address) {
+        storage::extend_instance(&e);
+        storage::get_admin(&e).require_auth();
+        new_admin.require_auth();
+
+        storage::set_admin(&e, &new_admin);
+        OracleAggregatorEvents::set_admin(&e, new_admin);
+    }
+
+    /// (Admin only) Set the guardian, who can pause the aggregator
+    ///
+    /// ### Arguments
+    /// * `guardian` - The address of the guardian
+    pub fn set_guardian(e: Env, guardian: Address) {
+        storage::extend_instance(&e);
+        storage::get_admin(&e).require_auth();
+
+        storage::set_guardian(&e, &guardian);
+        OracleAggregatorEvents::set_guardian(&e, guardian);
+    }
+
+    /// (Guardian only) Pause an asset, or every asset
+    ///
+    /// A paused asset is not priced, and is not listed by `assets`.
+    ///
+    /// ### Arguments
+    /// * `asset` - The asset to pause, or None to pause every asset
+    ///
+    /// ### Errors
+    /// * `GuardianNotSet` - No guardian is set
+    pub fn pause(e: Env, asset: Option<Asset>) {
+        storage::extend_instance(&e);
+        storage::get_guardian(&e)
+            .unwrap_or_else(|| panic_with_error!(&e, OracleAggregatorErrors::GuardianNotSet))
+            .require_auth();
+
+        let asset = asset.map(|asset| config::resolve_asset(&e, &asset));
+        match &asset {
+            Some(asset) => {
+                let mut paused_assets = storage::get_paused_assets(&e);
+                if !paused_assets
+                    .iter()
+                    .any(|paused| assets_equal(&paused, asset))
+                {
+                    paused_assets.push_back(asset.clone());
+                    storage::set_paused_assets(&e, &paused_assets);
+                }
+            }
+            None => storage::set_paused(&e, true),
+        }
+        OracleAggregatorEvents::pause(&e, asset);
+    }
+
+    /// (Admin only) Unpause an asset, or lift the pause of every asset
+    ///
+    /// Lifting the pause of every asset does not unpause assets that were paused individually.
+    ///
+    /// ### Arguments
+    /// * `asset` - The asset to unpause, or None to lift the pause of every asset
+    pub fn unpause(e: Env, asset: Option<Asset>) {
+        storage::extend_instance(&e);
+        storage::get_admin(&e).require_auth();
+
+        let asset = asset.map(|asset| config::resolve_asset(&e, &asset));
+        match &asset {
+            Some(asset) => {
+                let mut paused_assets = storage::get_paused_assets(&e);
+                if let Some(index) = paused_assets
+                    .iter()
+                    .position(|paused| assets_equal(&paused, asset))
+                {
+                    paused_assets.remove(index as u32);
+                    storage::set_paused_assets(&e, &paused_assets);
+                }
+            }
+            None => storage::set_paused(&e, false),
+        }
+        OracleAggregatorEvents::unpause(&e, asset);
+    }
+
+    /// (Admin only) Set the source oracles
+    ///
+    /// Every asset config must only use the new source oracles.
+    ///
+    /// ### Arguments
+    /// * `oracle_ids` - The addresses of the source oracles
+    ///
+    /// ### Errors
+    /// * `InvalidBaseAsset` - The base asset of a source oracle is not USDC, or USD with a USD conversion set
+    /// * `InvalidOracles` - No source oracles were provided
+    /// * `InvalidAssetOracle` - An asset config or the feed of a pegged asset uses an oracle that is not a source oracle
+    pub fn set_oracles(e: Env, oracle_ids: Vec<Address>) {
+        storage::extend_instance(&e);
+        storage::get_admin(&e).require_auth();
+
+        let configs = config::load_oracle_configs(&e, &oracle_ids, &storage::get_usdc(&e));
+        for asset in storage::get_assets(&e).iter() {
+            if let Some(asset_config) = storage::get_asset_config(&e, &asset) {
+                config::validate_asset_config(&e, &configs, &asset_config);
+            }
+        }
+        for pegged in storage::get_pegged_assets(&e).values().iter() {
+            if let PegFeed::Source(feed) = &pegged.feed {
+                config::validate_pegged_feed(&e, &configs, feed);
+            }
+        }
+        storage::set_oracle_configs(&e, &configs);
+        OracleAggregatorEvents::set_oracles(&e, oracle_ids);
+    }
+
+    /// (Admin only) Set the max age of a price
+    ///
+    /// ### Arguments
+    /// * `max_age` - The maximum time the oracle will look back for a price (in seconds)
+    ///
+    /// ### Errors
+    /// * `InvalidMaxAge` - The max age is not between 360 (6m) and 3600 (60m)
+    pub fn set_max_age(e: Env, max_age: u64) {
+        storage::extend_instance(&e);
+        storage::get_admin(&e).require_auth();
+
+        config::validate_max_age(&e, max_age);
+        storage::set_max_age(&e, &max_age);
+        OracleAggregatorEvents::set_max_age(&e, max_age);
+    }
+
+    /// (Admin only) Set the grace period a cached price can be served for by `lastprice_with_grace`
+    /// after it exceeds the max age
+    ///
+    /// ### Arguments
+    /// * `grace_period` - The grace period (in seconds)
+    pub fn set_grace_period(e: Env, grace_period: u64) {
+        storage::extend_instance(&e);
+        storage::get_admin(&e).require_auth();
+
+        storage::set_grace_period(&e, &grace_period);
+        OracleAggregatorEvents::set_grace_period(&e, grace_period);
+    }
+
+    /// (Admin only) Set the decimals the oracle will report in
+    ///
+    /// Every price stored in the aggregator's decimals, which are the fixed prices of pegged assets,
+    /// price bounds, and last known good prices, is rescaled to the new decimals.
+    ///
+    /// ### Arguments
+    /// * `decimals` - The decimals the oracle will report in
+    ///
+    /// ### Errors
+    /// * `ScalingOverflow` - A stored price does not fit in an i128 once rescaled
+    /// * `InvalidPeggedAsset` - The fixed price of a pegged asset is not positive once rescaled
+    pub fn set_decimals(e: Env, decimals: u32) {
+        storage::extend_instance(&e);
+        storage::get_admin(&e).require_auth();
+
+        config::rescale_stored_prices(&e, storage::get_decimals(&e), decimals);
+        storage::set_decimals(&e, &decimals);
+        OracleAggregatorEvents::set_decimals(&e, decimals);
+    }
+
+    /// (Admin only) Set the rounding applied when a price loses precision
+    ///
+    /// Prices are rounded when they are normalized to the aggregator's decimals, and when they are
+    /// converted to USD or to a cross price. Defaults to `Floor`.
+    ///
+    /// ### Arguments
+    /// * `rounding` - The rounding mode
+    pub fn set_rounding(e: Env, rounding: RoundingMode) {
+        storage::extend_instance(&e);
+        storage::get_admin(&e).require_auth();
+
+        storage::set_rounding(&e, &rounding);
+        OracleAggregatorEvents::set_rounding(&e, rounding);
+    }
+
+    /// (Admin only) Set the strategy used to query a source oracle for an older round when
+    /// `lastprice` does not return a price
+    ///
+    /// ### Arguments
+    /// * `retry_strategy` - The retry strategy
+    pub fn set_retry_strategy(e: Env, retry_strategy: RetryStrategy) {
+        storage::extend_instance(&e);
+        storage::get_admin(&e).require_auth();
+
+        storage::set_retry_strategy(&e, &retry_strategy);
+        OracleAggregatorEvents::set_retry_strategy(&e, retry_strategy);
+    }
+
+    /// (Admin only) Set the asset config of an asset
+    ///
+    /// ### Arguments
+    /// * `asset` - The asset
+    /// * `asset_config` - The asset config
+    ///
+    /// ### Errors
+    /// * `InvalidAssetOracle` - The asset config has no sources, or uses an oracle that is not a source oracle
+    /// * `InvalidMaxAge` - The max age is not between 360 (6m) and 3600 (60m)
+    pub fn set_asset_config(e: Env, asset: Asset, asset_config: AssetConfig) {
+        storage::extend_instance(&e);
+        storage::get_admin(&e).require_auth();
+
+        let asset = config::resolve_asset(&e, &asset);
+        let configs = storage::get_oracle_configs(&e);
+        config::set_asset_config(&e, &configs, &asset, &asset_config);
+        OracleAggregatorEvents::set_asset_config(&e, asset, asset_config);
+    }
+
+    /// (Admin only) Set the `Asset::Other` symbol a Stellar asset is known as by source oracles
+    ///
+    /// Every query for the symbol is resolved to the Stellar asset, including the USDC check and
+    /// the base asset of the source oracles. The alias of the Stellar asset, and any other alias of
+    /// the symbol, are replaced.
+    ///
+    /// ### Arguments
+    /// * `address` - The address of the Stellar asset
+    /// * `symbol` - The symbol, or None to remove the alias
+    ///
+    /// ### Errors
+    /// * `InvalidAlias` - The symbol already has an asset config, is a derived, pegged or path asset,
+    ///   or is paused, or has price bounds, a max deviation or a last known good price
+    pub fn set_alias(e: Env, address: Address, symbol: Option<Symbol>) {
+        storage::extend_instance(&e);
+        storage::get_admin(&e).require_auth();
+
+        if let Some(previous_symbol) = storage::get_alias_symbol(&e, &address) {
+            storage::remove_alias(&e, &address, &previous_symbol);
+        }
+        if let Some(symbol) = &symbol {
+            // configs and state keyed by the symbol would no longer be reachable
+            let asset = Asset::Other(symbol.clone());
+            if storage::get_asset_config(&e, &asset).is_some()
+                || storage::get_derived_asset(&e, &asset).is_some()
+                || storage::get_asset_path(&e, &asset).is_some()
+                || storage::get_pegged_assets(&e).contains_key(asset.clone())
+                || storage::get_paused_assets(&e)
+                    .iter()
+                    .any(|paused| assets_equal(&paused, &asset))
+                || storage::get_price_bounds(&e, &asset).is_some()
+                || storage::get_max_deviation(&e, &asset).is_some()
+                || storage::get_last_price(&e, &asset).is_some()
+            {
+                panic_with_error!(&e, OracleAggregatorErrors::InvalidAlias);
+            }
+            if let Some(previous_address) = storage::get_alias_address(&e, symbol) {
+                storage::remove_alias(&e, &previous_address, symbol);
+            }
+            storage::set_alias(&e, &address, symbol);
+        }
+        OracleAggregatorEvents::set_alias(&e, address, symbol);
+    }
+
+    /// (Admin only) Set the max deviation between consecutive rounds for an asset
+    ///
+    /// A price from a source oracle that moved more than the max deviation from the previous round
+    /// reported by the same oracle is rejected.
+    ///
+    /// ### Arguments
+    /// * `asset` - The asset
+    /// * `max_deviation` - The max deviation in basis points, or None to remove the limit
+    ///
+    /// ### Errors
+    /// * `InvalidMaxDeviation` - The max deviation is zero
+    pub fn set_max_deviation(e: Env, asset: Asset, max_deviation: Option<u32>) {
+        storage::extend_instance(&e);
+        storage::get_admin(&e).require_auth();
+
+        let asset = config::resolve_asset(&e, &asset);
+        if max_deviation == Some(0) {
+            panic_with_error!(&e, OracleAggregatorErrors::InvalidMaxDeviation);
+        }
+        storage::set_max_deviation(&e, &asset, &max_deviation);
+        OracleAggregatorEvents::set_max_deviation(&e, asset, max_deviation);
+    }
+
+    /// (Admin only) Set the price bounds of an asset
+    ///
+    /// A price of the asset outside of the bounds is rejected with `PriceOutOfBounds`. Prices that
+    /// are zero or negative are always rejected.
+    ///
+    /// ### Arguments
+    /// * `asset` - The asset
+    /// * `bounds` - The minimum and maximum price, in the aggregator's decimals, or None to remove the bounds
+    ///
+    /// ### Errors
+    /// * `InvalidAssetOracle` - The asset does not have an asset config, and is not USDC or a
+    ///   derived, pegged or path asset
+    /// * `InvalidPriceBounds` - The minimum price is not positive, or is greater than the maximum price
+    pub fn set_price_bounds(e: Env, asset: Asset, bounds: Option<PriceBounds>) {
+        storage::extend_instance(&e);
+        storage::get_admin(&e).require_auth();
+
+        let asset = config::resolve_asset(&e, &asset);
+        if let Some(bounds) = &bounds {
+            if !config::is_listed(&e, &asset) {
+                panic_with_error!(&e, OracleAggregatorErrors::InvalidAssetOracle);
+            }
+            config::validate_price_bounds(&e, bounds);
+        }
+        storage::set_price_bounds(&e, &asset, &bounds);
+        OracleAggregatorEvents::set_price_bounds(&e, asset, bounds);
+    }
+
+    /// (Admin only) Set the underlying asset a wrapped or bridged asset is priced from
+    ///
+    /// The derived asset is priced as the price of the underlying asset multiplied by the ratio,
+    /// in basis points, so a ratio below 10000 applies a haircut. The price bounds of the derived
+    /// asset are checked against the derived price.
+    ///
+    /// ### Arguments
+    /// * `asset` - The derived asset
+    /// * `derived` - The underlying asset and the ratio, or None to remove the derived asset
+    ///
+    /// ### Errors
+    /// * `InvalidDerivedAsset` - The asset is USDC, a pegged asset, priced through a path or has an
+    ///   asset config, the underlying asset does not have an asset config, or the ratio is zero
+    pub fn set_derived_asset(e: Env, asset: Asset, derived: Option<DerivedAsset>) {
+        storage::extend_instance(&e);
+        storage::get_admin(&e).require_auth();
+
+        let asset = config::resolve_asset(&e, &asset);
+        let derived = derived.map(|derived| DerivedAsset {
+            underlying: config::resolve_asset(&e, &derived.underlying),
+            ratio: derived.ratio,
+        });
+        config::set_derived_asset(&e, &asset, &derived);
+        OracleAggregatorEvents::set_derived_asset(&e, asset, derived);
+    }
+
+    /// (Admin only) Set the path of oracles an asset is priced through
+    ///
+    /// The asset is priced as the product of the price reported for each leg, so each leg must be
+    /// quoted in the asset of the next leg, and the last leg must be quoted in USDC. For example,
+    /// TOKEN/XLM from one oracle followed by XLM/USDC from another. The oracles do not need to be
+    /// source oracles.
+    ///
+    /// ### Arguments
+    /// * `asset` - The asset
+    /// * `path` - The oracle and asset identifier of each leg, in order, or None to remove the path
+    ///
+    /// ### Errors
+    /// * `InvalidAssetPath` - The asset is USDC, a derived or pegged asset, or has an asset config,
+    ///   the path is empty, the oracle of a leg does not report prices in the asset of the next
+    ///   leg, or the oracle of the last leg does not report prices in USDC
+    pub fn set_asset_path(e: Env, asset: Asset, path: Option<Vec<AssetSource>>) {
+        storage::extend_instance(&e);
+        storage::get_admin(&e).require_auth();
+
+        let asset = config::resolve_asset(&e, &asset);
+        let legs = path.as_ref().map(|path| config::load_asset_path(&e, path));
+        config::set_asset_path(&e, &asset, &legs);
+        OracleAggregatorEvents::set_asset_path(&e, asset, path);
+    }
+
+    /// (Admin only) Register a pegged asset, which is priced at a fixed price like USDC
+    ///
+    /// If the pegged asset has a feed, the fixed price is only returned while the price reported by
+    /// the feed is within the tolerance band around the fixed price. The fixed price is converted
+    /// to USD like every other price if a USDC source is set.
+    ///
+    /// ### Arguments
+    /// * `asset` - The pegged asset
+    /// * `pegged` - The fixed price in USDC, in the aggregator's decimals, and the feed and
+    ///   tolerance the peg is checked against, or None to remove the pegged asset
+    ///
+    /// ### Errors
+    /// * `InvalidPeggedAsset` - The asset is USDC, a derived asset, priced through a path or has an
+    ///   asset config, or the fixed price is not positive
+    /// * `InvalidAssetOracle` - The feed is not a source oracle
+    pub fn set_pegged_asset(e: Env, asset: Asset, pegged: Option<PeggedAsset>) {
+        storage::extend_instance(&e);
+        storage::get_admin(&e).require_auth();
+
+        let asset = config::resolve_asset(&e, &asset);
+        let configs = storage::get_oracle_configs(&e);
+        config::set_pegged_asset(&e, &configs, &asset, &pegged);
+        OracleAggregatorEvents::set_pegged_asset(&e, asset, pegged);
+    }
+
+    /// (Admin only) Set how prices reported in USD by source oracles are converted to USDC
+    ///
+    /// Source oracles that report prices in USD, like the Reflector CEX and FX oracles, can only be
+    /// set with `set_oracles` while a USD conversion is set. Their prices are converted to USDC
+    /// before they are normalized, either 1:1 or with the price of USDC in USD reported by an
+    /// oracle.
+    ///
+    /// ### Arguments
+    /// * `conversion` - The USD conversion, or None to remove it
+    ///
+    /// ### Errors
+    /// * `InvalidBaseAsset` - The USD conversion is removed while a source oracle reports prices in
+    ///   USD, or its oracle does not report prices in USD
+    pub fn set_usd_conversion(e: Env, conversion: Option<UsdConversion>) {
+        storage::extend_instance(&e);
+        storage::get_admin(&e).require_auth();
+
+        if conversion.is_none()
+            && storage::get_oracle_configs(&e)
+                .iter()
+                .any(|config| config::is_usd(&e, &config.base))
+        {
+            panic_with_error!(&e, OracleAggregatorErrors::InvalidBaseAsset);
+        }
+        let usd_conversion = conversion
+            .as_ref()
+            .map(|conversion| config::load_usd_conversion(&e, conversion));
+        storage::set_usd_conversion(&e, &usd_conversion);
+        OracleAggregatorEvents::set_usd_conversion(&e, conversion);
+    }
+
+    /// (Admin only) Set the source of the price of USDC in USD
+    ///
+    /// If a USDC source is set, USDC is priced by the source and every other price is converted
+    /// from USDC to USD. If None, USDC is priced at 1 and prices are reported in USDC.
+    ///
+    /// ### Arguments
+    /// * `source` - The oracle that reports the price of USDC in USD, and the asset identifier it uses for USDC
+    ///
+    /// ### Errors
+    /// * `InvalidBaseAsset` - The oracle does not report prices in USD
+    pub fn set_usdc_source(e: Env, source: Option<AssetSource>) {
+        storage::extend_instance(&e);
+        storage::get_admin(&e).require_auth();
+
+        let usdc_source = source
+            .clone()
+            .map(|source| config::load_usdc_source(&e, &source));
+        storage::set_usdc_source(&e, &usdc_source);
+        OracleAggregatorEvents::set_usdc_source(&e, source);
+    }
+
+    /// (Admin only) Upgrade the contract to a new WASM
+    ///
+    /// If the new WASM changes the storage schema, `migrate` must be called after the upgrade.
+    /// Until then, the price methods fail with `InvalidSchemaVersion`.
+    ///
+    /// ### Arguments
+    /// * `wasm_hash` - The hash of the new WASM, which must already be uploaded
+    pub fn upgrade(e: Env, wasm_hash: BytesN<32>) {
+        storage::extend_instance(&e);
+        storage::get_admin(&e).require_auth();
+
+        e.deployer().update_current_contract_wasm(wasm_hash.clone());
+        OracleAggregatorEvents::upgrade(&e, wasm_hash);
+    }
+
+    /// (Admin only) Migrate the storage to the schema version of the current WASM
+    ///
+    /// ### Errors
+    /// * `InvalidSchemaVersion` - The storage schema is newer than the current WASM supports
+    pub fn migrate(e: Env) {
+        storage::extend_instance(&e);
+        storage::get_admin(&e).require_auth();
+
+        upgrade::migrate(&e);
+        OracleAggregatorEvents::migrate(&e, upgrade::SCHEMA_VERSION);
+    }
+
+    /// Fetch the version of the contract and its storage schema
+    pub fn version(e: Env) -> Version {
+        Version {
+            contract: String::from_str(&e, env!("CARGO_PKG_VERSION")),
+            schema: storage::get_schema_version(&e),
+        }
+    }
+
+    /// Fetch the admin address
+    pub fn admin(e: Env) -> Address {
+        storage::get_admin(&e)
+    }
+
+    /// Fetch the guardian address, if it exists
+    pub fn guardian(e: Env) -> Option<Address> {
+        storage::get_guardian(&e)
+    }
+
+    /// Fetch if an asset is paused, either individually, because every asset is paused, or because
+    /// it is derived from a paused asset
+    pub fn is_paused(e: Env, asset: Asset) -> bool {
+        let asset = config::resolve_asset(&e, &asset);
+        config::is_paused(&e, &asset)
+    }
+
+    /// Fetch if every asset is paused
+    pub fn paused(e: Env) -> bool {
+        storage::get_paused(&e)
+    }
+
     /// Fetch the max age of a price
     pub fn max_age(e: Env) -> u64 {
         storage::get_max_age(&e)
     }
 
-    pub fn config(e: Env) -> OracleConfig {
-        storage::get_oracle_config(&e)
+    /// Fetch the rounding applied when a price loses precision
+    pub fn rounding(e: Env) -> RoundingMode {
+        storage::get_rounding(&e)
+    }
+
+    /// Fetch the strategy used to query a source oracle for an older round
+    pub fn retry_strategy(e: Env) -> RetryStrategy {
+        storage::get_retry_strategy(&e)
+    }
+
+    /// Fetch the grace period a cached price can be served for after it exceeds the max age
+    pub fn grace_period(e: Env) -> u64 {
+        storage::get_grace_period(&e)
+    }
+
+    /// Fetch the configs of the source oracles
+    pub fn config(e: Env) -> Vec<OracleConfig> {
+        storage::get_oracle_configs(&e)
+    }
+
+    /// Fetch the asset config of an asset
+    ///
+    /// ### Errors
+    /// * `InvalidAssetOracle` - The asset does not have an asset config
+    pub fn asset_config(e: Env, asset: Asset) -> AssetConfig {
+        let asset = config::resolve_asset(&e, &asset);
+        match storage::get_asset_config(&e, &asset) {
+            Some(config) => config,
+            None => panic_with_error!(&e, OracleAggregatorErrors::InvalidAssetOracle),
+        }
+    }
+
+    /// Fetch the `Asset::Other` symbol a Stellar asset is aliased to, if it exists
+    pub fn alias(e: Env, address: Address) -> Option<Symbol> {
+        storage::get_alias_symbol(&e, &address)
+    }
+
+    /// Fetch the Stellar asset an `Asset::Other` symbol is an alias of, if it exists
+    pub fn alias_address(e: Env, symbol: Symbol) -> Option<Address> {
+        storage::get_alias_address(&e, &symbol)
+    }
+
+    /// Fetch the max deviation between consecutive rounds for an asset, in basis points
+    pub fn max_deviation(e: Env, asset: Asset) -> Option<u32> {
+        let asset = config::resolve_asset(&e, &asset);
+        storage::get_max_deviation(&e, &asset)
+    }
+
+    /// Fetch the most recent price of each asset
+    ///
+    /// Returns the same prices as calling `lastprice` for each asset, in the same order as
+    /// `assets`, while loading the config of the aggregator once.
+    ///
+    /// ### Arguments
+    /// * `assets` - The assets to fetch the prices for
+    ///
+    /// ### Errors
+    /// * `InvalidAssetOracle` - An asset does not have an asset config
+    pub fn lastprices(e: Env, assets: Vec<Asset>) -> Vec<Option<PriceData>> {
+        upgrade::require_current_schema(&e);
+        get_last_prices(&e, &assets)
+    }
+
+    /// Fetch the most recent price of a base asset in a quote asset
+    ///
+    /// The cross price is derived from the price of each asset in USDC, is reported in the
+    /// aggregator's decimals, and uses the older of the two timestamps. Either asset can be USDC.
+    ///
+    /// ### Arguments
+    /// * `base_asset` - The asset to fetch the price for
+    /// * `quote_asset` - The asset to price the base asset in
+    ///
+    /// ### Errors
+    /// * `InvalidAssetOracle` - An asset does not have an asset config
+    /// * `PriceOutOfBounds` - The price of an asset is not positive, or is outside its price bounds
+    pub fn x_lastprice(e: Env, base_asset: Asset, quote_asset: Asset) -> Option<PriceData> {
+        upgrade::require_current_schema(&e);
+        let base_asset = config::resolve_asset(&e, &base_asset);
+        let quote_asset = config::resolve_asset(&e, &quote_asset);
+        if config::is_paused(&e, &base_asset) || config::is_paused(&e, &quote_asset) {
+            return None;
+        }
+        let base_price = lastprice_in_usdc(&e, &base_asset)?;
+        let quote_price = lastprice_in_usdc(&e, &quote_asset)?;
+        cross_price(&e, &base_price, &quote_price)
+    }
+
+    /// Fetch the time-weighted average price of an asset over its most recent rounds
+    ///
+    /// The rounds are read with `prices` and normalized to the aggregator's decimals. Each round
+    /// is weighted by the time until the next round.
+    ///
+    /// ### Arguments
+    /// * `asset` - The asset to fetch the price for
+    /// * `records` - The number of rounds to average
+    ///
+    /// ### Errors
+    /// * `InvalidAssetOracle` - The asset does not have an asset config
+    /// * `InsufficientRounds` - Fewer than `records` rounds are at most `max_age` old, or `records` is
+    ///   over `MAX_RECORDS`
+    /// * `AssetPaused` - The asset is paused
+    pub fn twap(e: Env, asset: Asset, records: u32) -> PriceData {
+        upgrade::require_current_schema(&e);
+        let asset = config::resolve_asset(&e, &asset);
+        if config::is_paused(&e, &asset) {
+            panic_with_error!(&e, OracleAggregatorErrors::AssetPaused);
+        }
+        let resolution = config::get_resolution(&e);
+        let (prices, max_age) = if is_usdc(&e, &asset) {
+            (
+                get_usdc_prices(&e, records, resolution),
+                storage::get_max_age(&e),
+            )
+        } else {
+            (
+                get_prices(&e, &asset, records),
+                get_asset_max_age(&e, &asset),
+            )
+        };
+        let prices = prices.unwrap_or_else(|| Vec::new(&e));
+        twap::time_weighted_average(&e, &prices, records, max_age, resolution)
+    }
+
+    /// Fetch the underlying asset and ratio a derived asset is priced from, if it exists
+    pub fn derived_asset(e: Env, asset: Asset) -> Option<DerivedAsset> {
+        let asset = config::resolve_asset(&e, &asset);
+        storage::get_derived_asset(&e, &asset)
+    }
+
+    /// Fetch the assets priced from an underlying asset
+    ///
+    /// Each derived asset is also included in `assets()`.
+    pub fn derived_assets(e: Env) -> Vec<Asset> {
+        storage::get_derived_assets(&e)
+    }
+
+    /// Fetch the path of oracles an asset is priced through, if it exists
+    pub fn asset_path(e: Env, asset: Asset) -> Option<Vec<PathLeg>> {
+        let asset = config::resolve_asset(&e, &asset);
+        storage::get_asset_path(&e, &asset)
+    }
+
+    /// Fetch the assets priced through a path of oracles
+    ///
+    /// Each path asset is also included in `assets()`.
+    pub fn path_assets(e: Env) -> Vec<Asset> {
+        storage::get_path_assets(&e)
+    }
+
+    /// Fetch the fixed price, feed and tolerance of a pegged asset, if it exists
+    pub fn pegged_asset(e: Env, asset: Asset) -> Option<PeggedAsset> {
+        let asset = config::resolve_asset(&e, &asset);
+        storage::get_pegged_assets(&e).get(asset)
+    }
+
+    /// Fetch the pegged assets
+    ///
+    /// Each pegged asset is also included in `assets()`.
+    pub fn pegged_assets(e: Env) -> Vec<Asset> {
+        storage::get_pegged_assets(&e).keys()
+    }
+
+    /// Fetch the price bounds of an asset, if they exist
+    pub fn price_bounds(e: Env, asset: Asset) -> Option<PriceBounds> {
+        let asset = config::resolve_asset(&e, &asset);
+        storage::get_price_bounds(&e, &asset)
+    }
+
+    /// Fetch the most recent price of an asset, rounded with `rounding` instead of the configured
+    /// rounding
+    ///
+    /// This lets a caller round the price of the same asset down when it is used as collateral,
+    /// and up when it is used as a liability. The price is not cached as the last known good price.
+    ///
+    /// ### Arguments
+    /// * `asset` - The asset to fetch the price for
+    /// * `rounding` - The rounding applied when the price loses precision
+    ///
+    /// ### Errors
+    /// * `InvalidAssetOracle` - The asset does not have an asset config
+    /// * `PriceOutOfBounds` - The price is not positive, or is outside the price bounds of the asset
+    pub fn lastprice_rounded(e: Env, asset: Asset, rounding: RoundingMode) -> Option<PriceData> {
+        upgrade::require_current_schema(&e);
+        let asset = config::resolve_asset(&e, &asset);
+        if config::is_paused(&e, &asset) {
+            return None;
+        }
+        if is_usdc(&e, &asset) {
+            return get_usdc_price(&e, rounding);
+        }
+
+        get_rounded_price(&e, &asset, rounding)
+    }
+
+    /// Fetch the most recent price of an asset, along with the oracles that reported it
+    ///
+    /// ### Arguments
+    /// * `asset` - The asset to fetch the price for
+    ///
+    /// ### Errors
+    /// * `InvalidAssetOracle` - The asset does not have an asset config
+    pub fn lastprice_with_source(e: Env, asset: Asset) -> Option<SourcedPrice> {
+        upgrade::require_current_schema(&e);
+        let asset = config::resolve_asset(&e, &asset);
+        if config::is_paused(&e, &asset) {
+            return None;
+        }
+        if is_usdc(&e, &asset) {
+            // if the asset is USDC, the price is only reported by the USDC source, if it exists
+            let mut sources = Vec::new(&e);
+            if let Some(source) = storage::get_usdc_source(&e) {
+                sources.push_back(source.oracle.oracle_id);
+            }
+            return get_usdc_price(&e, storage::get_rounding(&e)).map(|price| SourcedPrice {
+                price,
+                sources,
+                fallback: None,
+            });
+        }
+
+        get_sourced_price(&e, &asset)
+    }
+
+    /// Fetch the most recent price of an asset, along with how it was resolved
+    ///
+    /// The detail includes the source oracle that answered for the price, whether `lastprice`, the
+    /// `price` retry or the `prices` retry answered, how many `resolution` steps back the answer
+    /// came from, the age of the price, and how many source oracles agreed on it. If the price is the median of
+    /// several source oracles, the answer is the source oracle that reported the median price.
+    ///
+    /// ### Arguments
+    /// * `asset` - The asset to fetch the price for
+    ///
+    /// ### Errors
+    /// * `InvalidAssetOracle` - The asset does not have an asset config
+    pub fn lastprice_detail(e: Env, asset: Asset) -> Option<PriceDetail> {
+        upgrade::require_current_schema(&e);
+        let asset = config::resolve_asset(&e, &asset);
+        if config::is_paused(&e, &asset) {
+            return None;
+        }
+        if is_usdc(&e, &asset) {
+            return get_usdc_price_detail(&e);
+        }
+
+        get_price_detail(&e, &asset)
+    }
+
+    /// Fetch the most recent price of an asset, or its last known good price if no source has a
+    /// price at most `max_age` old
+    ///
+    /// While the grace period is set, every successful price fetched by `lastprice`, `lastprices`,
+    /// `lastprice_with_source` or `lastprice_detail` is cached. The cached price is served until
+    /// the grace period after `max_age` has passed, and is marked as stale.
+    ///
+    /// ### Arguments
+    /// * `asset` - The asset to fetch the price for
+    ///
+    /// ### Errors
+    /// * `InvalidAssetOracle` - The asset does not have an asset config
+    pub fn lastprice_with_grace(e: Env, asset: Asset) -> Option<GracePrice> {
+        upgrade::require_current_schema(&e);
+        let asset = config::resolve_asset(&e, &asset);
+        if config::is_paused(&e, &asset) {
+            return None;
+        }
+        if is_usdc(&e, &asset) {
+            // USDC is not cached
+            return get_usdc_price(&e, storage::get_rounding(&e)).map(|price| GracePrice {
+                price,
+                stale: false,
+            });
+        }
+
+        get_grace_price(&e, &asset)
+    }
+
+    /// Fetch the conversion of prices reported in USD by source oracles to USDC, if it exists
+    pub fn usd_conversion(e: Env) -> Option<UsdConversionConfig> {
+        storage::get_usd_conversion(&e)
+    }
+
+    /// Fetch the source of the price of USDC in USD, if it exists
+    pub fn usdc_source(e: Env) -> Option<UsdcSource> {
+        storage::get_usdc_source(&e)
     }
 
     pub fn usdc(e: Env) -> Address {
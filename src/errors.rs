@@ -1,9 +1,22 @@
 use soroban_sdk::contracterror;
 #[contracterror]
-#[derive(Copy, Clone, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum OracleAggregatorErrors {
     NotImplemented = 100,
     InvalidAssetOracle = 101,
     InvalidMaxAge = 102,
     InvalidBaseAsset = 103,
+    InvalidOracles = 104,
+    InvalidSchemaVersion = 105,
+    InvalidMaxDeviation = 106,
+    InsufficientRounds = 107,
+    AssetPaused = 108,
+    InvalidPriceBounds = 109,
+    PriceOutOfBounds = 110,
+    ScalingOverflow = 111,
+    InvalidAlias = 112,
+    InvalidDerivedAsset = 113,
+    InvalidPeggedAsset = 114,
+    InvalidAssetPath = 115,
+    GuardianNotSet = 116,
 }
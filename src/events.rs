@@ -0,0 +1,297 @@
+use sep_40_oracle::Asset;
+use soroban_sdk::{Address, BytesN, Env, Symbol, Vec};
+
+use crate::types::{
+    AssetConfig, AssetSource, DerivedAsset, PeggedAsset, PriceBounds, RetryStrategy, RoundingMode,
+    UsdConversion,
+};
+
+pub struct OracleAggregatorEvents {}
+
+impl OracleAggregatorEvents {
+    /// Emitted when the contract is constructed
+    ///
+    /// - topics - `["init", admin: Address]`
+    /// - data - `[oracle_ids: Vec<Address>, usdc: Address, decimals: u32, max_age: u64, usd_conversion: Option<UsdConversion>]`
+    pub fn init(
+        e: &Env,
+        admin: Address,
+        oracle_ids: Vec<Address>,
+        usdc: Address,
+        decimals: u32,
+        max_age: u64,
+        usd_conversion: Option<UsdConversion>,
+    ) {
+        let topics = (Symbol::new(e, "init"), admin);
+        e.events().publish(
+            topics,
+            (oracle_ids, usdc, decimals, max_age, usd_conversion),
+        );
+    }
+
+    /// Emitted when the admin is changed
+    ///
+    /// - topics - `["set_admin", new_admin: Address]`
+    /// - data - `()`
+    pub fn set_admin(e: &Env, new_admin: Address) {
+        let topics = (Symbol::new(e, "set_admin"), new_admin);
+        e.events().publish(topics, ());
+    }
+
+    /// Emitted when the guardian is changed
+    ///
+    /// - topics - `["set_guardian", guardian: Address]`
+    /// - data - `()`
+    pub fn set_guardian(e: &Env, guardian: Address) {
+        let topics = (Symbol::new(e, "set_guardian"), guardian);
+        e.events().publish(topics, ());
+    }
+
+    /// Emitted when an asset, or every asset, is paused
+    ///
+    /// - topics - `["pause"]`
+    /// - data - `asset: Option<Asset>`
+    ///
+    /// `asset` is None if every asset is paused
+    pub fn pause(e: &Env, asset: Option<Asset>) {
+        let topics = (Symbol::new(e, "pause"),);
+        e.events().publish(topics, asset);
+    }
+
+    /// Emitted when an asset, or every asset, is unpaused
+    ///
+    /// - topics - `["unpause"]`
+    /// - data - `asset: Option<Asset>`
+    ///
+    /// `asset` is None if the global pause is lifted
+    pub fn unpause(e: &Env, asset: Option<Asset>) {
+        let topics = (Symbol::new(e, "unpause"),);
+        e.events().publish(topics, asset);
+    }
+
+    /// Emitted when the source oracles are changed
+    ///
+    /// - topics - `["set_oracles"]`
+    /// - data - `oracle_ids: Vec<Address>`
+    pub fn set_oracles(e: &Env, oracle_ids: Vec<Address>) {
+        let topics = (Symbol::new(e, "set_oracles"),);
+        e.events().publish(topics, oracle_ids);
+    }
+
+    /// Emitted when the max age of a price is changed
+    ///
+    /// - topics - `["set_max_age"]`
+    /// - data - `max_age: u64`
+    pub fn set_max_age(e: &Env, max_age: u64) {
+        let topics = (Symbol::new(e, "set_max_age"),);
+        e.events().publish(topics, max_age);
+    }
+
+    /// Emitted when the grace period of cached prices is changed
+    ///
+    /// - topics - `["set_grace_period"]`
+    /// - data - `grace_period: u64`
+    pub fn set_grace_period(e: &Env, grace_period: u64) {
+        let topics = (Symbol::new(e, "set_grace_period"),);
+        e.events().publish(topics, grace_period);
+    }
+
+    /// Emitted when the decimals are changed
+    ///
+    /// - topics - `["set_decimals"]`
+    /// - data - `decimals: u32`
+    pub fn set_decimals(e: &Env, decimals: u32) {
+        let topics = (Symbol::new(e, "set_decimals"),);
+        e.events().publish(topics, decimals);
+    }
+
+    /// Emitted when the rounding is changed
+    ///
+    /// - topics - `["set_rounding"]`
+    /// - data - `rounding: RoundingMode`
+    pub fn set_rounding(e: &Env, rounding: RoundingMode) {
+        let topics = (Symbol::new(e, "set_rounding"),);
+        e.events().publish(topics, rounding);
+    }
+
+    /// Emitted when the retry strategy is changed
+    ///
+    /// - topics - `["set_retry_strategy"]`
+    /// - data - `retry_strategy: RetryStrategy`
+    pub fn set_retry_strategy(e: &Env, retry_strategy: RetryStrategy) {
+        let topics = (Symbol::new(e, "set_retry_strategy"),);
+        e.events().publish(topics, retry_strategy);
+    }
+
+    /// Emitted when the asset config of an asset is set
+    ///
+    /// - topics - `["set_asset_config", asset: Asset]`
+    /// - data - `asset_config: AssetConfig`
+    pub fn set_asset_config(e: &Env, asset: Asset, asset_config: AssetConfig) {
+        let topics = (Symbol::new(e, "set_asset_config"), asset);
+        e.events().publish(topics, asset_config);
+    }
+
+    /// Emitted when the alias of a Stellar asset is set or removed
+    ///
+    /// - topics - `["set_alias", address: Address]`
+    /// - data - `symbol: Option<Symbol>`
+    pub fn set_alias(e: &Env, address: Address, symbol: Option<Symbol>) {
+        let topics = (Symbol::new(e, "set_alias"), address);
+        e.events().publish(topics, symbol);
+    }
+
+    /// Emitted when the max deviation of an asset is set or removed
+    ///
+    /// - topics - `["set_max_deviation", asset: Asset]`
+    /// - data - `max_deviation: Option<u32>`
+    pub fn set_max_deviation(e: &Env, asset: Asset, max_deviation: Option<u32>) {
+        let topics = (Symbol::new(e, "set_max_deviation"), asset);
+        e.events().publish(topics, max_deviation);
+    }
+
+    /// Emitted when the price bounds of an asset are set or removed
+    ///
+    /// - topics - `["set_price_bounds", asset: Asset]`
+    /// - data - `bounds: Option<PriceBounds>`
+    pub fn set_price_bounds(e: &Env, asset: Asset, bounds: Option<PriceBounds>) {
+        let topics = (Symbol::new(e, "set_price_bounds"), asset);
+        e.events().publish(topics, bounds);
+    }
+
+    /// Emitted when the underlying asset of a derived asset is set or removed
+    ///
+    /// - topics - `["set_derived_asset", asset: Asset]`
+    /// - data - `derived: Option<DerivedAsset>`
+    pub fn set_derived_asset(e: &Env, asset: Asset, derived: Option<DerivedAsset>) {
+        let topics = (Symbol::new(e, "set_derived_asset"), asset);
+        e.events().publish(topics, derived);
+    }
+
+    /// Emitted when the path of oracles an asset is priced through is set or removed
+    ///
+    /// - topics - `["set_asset_path", asset: Asset]`
+    /// - data - `path: Option<Vec<AssetSource>>`
+    pub fn set_asset_path(e: &Env, asset: Asset, path: Option<Vec<AssetSource>>) {
+        let topics = (Symbol::new(e, "set_asset_path"), asset);
+        e.events().publish(topics, path);
+    }
+
+    /// Emitted when a pegged asset is registered, updated or removed
+    ///
+    /// - topics - `["set_pegged_asset", asset: Asset]`
+    /// - data - `pegged: Option<PeggedAsset>`
+    pub fn set_pegged_asset(e: &Env, asset: Asset, pegged: Option<PeggedAsset>) {
+        let topics = (Symbol::new(e, "set_pegged_asset"), asset);
+        e.events().publish(topics, pegged);
+    }
+
+    /// Emitted when the conversion of prices reported in USD to USDC is set or removed
+    ///
+    /// - topics - `["set_usd_conversion"]`
+    /// - data - `conversion: Option<UsdConversion>`
+    pub fn set_usd_conversion(e: &Env, conversion: Option<UsdConversion>) {
+        let topics = (Symbol::new(e, "set_usd_conversion"),);
+        e.events().publish(topics, conversion);
+    }
+
+    /// Emitted when the USDC source is set or removed
+    ///
+    /// - topics - `["set_usdc_source"]`
+    /// - data - `source: Option<AssetSource>`
+    pub fn set_usdc_source(e: &Env, source: Option<AssetSource>) {
+        let topics = (Symbol::new(e, "set_usdc_source"),);
+        e.events().publish(topics, source);
+    }
+
+    /// Emitted when the contract is upgraded
+    ///
+    /// - topics - `["upgrade"]`
+    /// - data - `wasm_hash: BytesN<32>`
+    pub fn upgrade(e: &Env, wasm_hash: BytesN<32>) {
+        let topics = (Symbol::new(e, "upgrade"),);
+        e.events().publish(topics, wasm_hash);
+    }
+
+    /// Emitted when the storage is migrated to a new schema version
+    ///
+    /// - topics - `["migrate"]`
+    /// - data - `schema_version: u32`
+    pub fn migrate(e: &Env, schema_version: u32) {
+        let topics = (Symbol::new(e, "migrate"),);
+        e.events().publish(topics, schema_version);
+    }
+
+    /// Emitted when a source oracle did not have a last price, and an older round was found
+    /// with the retry strategy instead
+    ///
+    /// - topics - `["price_retry", asset: Asset]`
+    /// - data - `[oracle_id: Address, steps: u32, timestamp: u64]`
+    ///
+    /// `asset` is the asset identifier used by the source oracle, and `steps` is the number of
+    /// `resolution` periods the aggregator went back to find the price
+    pub fn price_retry(e: &Env, asset: Asset, oracle_id: Address, steps: u32, timestamp: u64) {
+        let topics = (Symbol::new(e, "price_retry"), asset);
+        e.events().publish(topics, (oracle_id, steps, timestamp));
+    }
+
+    /// Emitted when no source oracle of an asset had a valid price, and a fallback oracle
+    /// reported the price instead
+    ///
+    /// - topics - `["price_fallback", asset: Asset]`
+    /// - data - `[oracle_id: Address, index: u32]`
+    ///
+    /// `index` is the position of the fallback oracle in the fallbacks of the asset config
+    pub fn price_fallback(e: &Env, asset: Asset, oracle_id: Address, index: u32) {
+        let topics = (Symbol::new(e, "price_fallback"), asset);
+        e.events().publish(topics, (oracle_id, index));
+    }
+
+    /// Emitted when a source oracle does not have a price at most `max_age` old
+    ///
+    /// - topics - `["price_stale", asset: Asset]`
+    /// - data - `[oracle_id: Address, timestamp: Option<u64>]`
+    ///
+    /// `asset` is the asset identifier used by the source oracle, and `timestamp` is the timestamp
+    /// of the stale price, if one was found
+    pub fn price_stale(e: &Env, asset: Asset, oracle_id: Address, timestamp: Option<u64>) {
+        let topics = (Symbol::new(e, "price_stale"), asset);
+        e.events().publish(topics, (oracle_id, timestamp));
+    }
+
+    /// Emitted when no source or fallback oracle has a valid price for an asset, and None is returned
+    ///
+    /// - topics - `["price_none", asset: Asset]`
+    /// - data - `()`
+    pub fn price_none(e: &Env, asset: Asset) {
+        let topics = (Symbol::new(e, "price_none"), asset);
+        e.events().publish(topics, ());
+    }
+
+    /// Emitted when a price from a source oracle is rejected for moving more than the max deviation
+    /// from the previous round
+    ///
+    /// - topics - `["price_deviation", asset: Asset]`
+    /// - data - `[oracle_id: Address, previous_price: i128, price: i128]`
+    pub fn price_deviation(
+        e: &Env,
+        asset: Asset,
+        oracle_id: Address,
+        previous_price: i128,
+        price: i128,
+    ) {
+        let topics = (Symbol::new(e, "price_deviation"), asset);
+        e.events()
+            .publish(topics, (oracle_id, previous_price, price));
+    }
+
+    /// Emitted when a pegged asset is not priced because the price reported by its feed moved
+    /// outside of the tolerance band around the fixed price
+    ///
+    /// - topics - `["price_depeg", asset: Asset]`
+    /// - data - `[oracle_id: Address, fixed_price: i128, price: i128]`
+    pub fn price_depeg(e: &Env, asset: Asset, oracle_id: Address, fixed_price: i128, price: i128) {
+        let topics = (Symbol::new(e, "price_depeg"), asset);
+        e.events().publish(topics, (oracle_id, fixed_price, price));
+    }
+}
@@ -0,0 +1,179 @@
+use soroban_sdk::{Env, I256};
+
+use crate::{errors::OracleAggregatorErrors, types::RoundingMode};
+
+/// Fetch the scalar for a number of decimals, `10^decimals`
+///
+/// ### Errors
+/// * `ScalingOverflow` - The scalar does not fit in an i128
+pub fn scalar(decimals: u32) -> Result<i128, OracleAggregatorErrors> {
+    10i128
+        .checked_pow(decimals)
+        .ok_or(OracleAggregatorErrors::ScalingOverflow)
+}
+
+/// Scale a value from `from_decimals` to `to_decimals`
+///
+/// If decimals are removed, the value is rounded with `rounding`.
+///
+/// ### Errors
+/// * `ScalingOverflow` - The scalar or the scaled value does not fit in an i128
+pub fn scale(
+    value: i128,
+    from_decimals: u32,
+    to_decimals: u32,
+    rounding: RoundingMode,
+) -> Result<i128, OracleAggregatorErrors> {
+    if from_decimals > to_decimals {
+        let scalar = scalar(from_decimals - to_decimals)?;
+        Ok(round(
+            value.div_euclid(scalar),
+            value.rem_euclid(scalar),
+            scalar,
+            rounding,
+        ))
+    } else if from_decimals < to_decimals {
+        let scalar = scalar(to_decimals - from_decimals)?;
+        value
+            .checked_mul(scalar)
+            .ok_or(OracleAggregatorErrors::ScalingOverflow)
+    } else {
+        Ok(value)
+    }
+}
+
+/// Calculate `x * y / z`, rounded with `rounding`, without overflowing on the intermediate product
+///
+/// The product is computed in 128 bits when it fits, and in 256 bits otherwise.
+///
+/// ### Errors
+/// * `ScalingOverflow` - `z` is not positive, or the result does not fit in an i128
+pub fn mul_div(
+    e: &Env,
+    x: i128,
+    y: i128,
+    z: i128,
+    rounding: RoundingMode,
+) -> Result<i128, OracleAggregatorErrors> {
+    if z <= 0 {
+        return Err(OracleAggregatorErrors::ScalingOverflow);
+    }
+    match x.checked_mul(y) {
+        Some(product) => Ok(round(
+            product.div_euclid(z),
+            product.rem_euclid(z),
+            z,
+            rounding,
+        )),
+        None => {
+            let product = I256::from_i128(e, x).mul(&I256::from_i128(e, y));
+            let z_256 = I256::from_i128(e, z);
+            let remainder = product.rem_euclid(&z_256);
+            // the remainder is less than `z`, so it fits in an i128
+            let quotient = product
+                .sub(&remainder)
+                .div(&z_256)
+                .to_i128()
+                .ok_or(OracleAggregatorErrors::ScalingOverflow)?;
+            Ok(round(
+                quotient,
+                remainder.to_i128().unwrap_or_default(),
+                z,
+                rounding,
+            ))
+        }
+    }
+}
+
+/// Round the result of a division, given the floored quotient and the non-negative remainder
+fn round(quotient: i128, remainder: i128, divisor: i128, rounding: RoundingMode) -> i128 {
+    if remainder == 0 {
+        return quotient;
+    }
+    match rounding {
+        RoundingMode::Floor => quotient,
+        RoundingMode::Ceil => quotient + 1,
+        RoundingMode::HalfEven => {
+            let half = divisor - remainder;
+            if remainder > half || (remainder == half && quotient % 2 != 0) {
+                quotient + 1
+            } else {
+                quotient
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_scale() {
+        assert_eq!(scale(1_2345678, 7, 6, RoundingMode::Floor), Ok(1_234567));
+        assert_eq!(scale(1_2345678, 7, 6, RoundingMode::Ceil), Ok(1_234568));
+        assert_eq!(scale(1_2345678, 7, 6, RoundingMode::HalfEven), Ok(1_234568));
+        assert_eq!(scale(1_2345678, 7, 8, RoundingMode::Ceil), Ok(1_23456780));
+        assert_eq!(scale(1_2345678, 7, 7, RoundingMode::Ceil), Ok(1_2345678));
+
+        // half-even rounds ties to the even neighbor
+        assert_eq!(scale(1_25, 2, 1, RoundingMode::HalfEven), Ok(1_2));
+        assert_eq!(scale(1_35, 2, 1, RoundingMode::HalfEven), Ok(1_4));
+        assert_eq!(scale(1_251, 3, 1, RoundingMode::HalfEven), Ok(1_3));
+        assert_eq!(scale(-1_25, 2, 1, RoundingMode::HalfEven), Ok(-1_2));
+
+        // floor and ceil round toward negative and positive infinity
+        assert_eq!(scale(-1_21, 2, 1, RoundingMode::Floor), Ok(-1_3));
+        assert_eq!(scale(-1_21, 2, 1, RoundingMode::Ceil), Ok(-1_2));
+
+        assert_eq!(
+            scale(1, 0, 39, RoundingMode::Floor),
+            Err(OracleAggregatorErrors::ScalingOverflow)
+        );
+        assert_eq!(
+            scale(1, 39, 0, RoundingMode::Floor),
+            Err(OracleAggregatorErrors::ScalingOverflow)
+        );
+        assert_eq!(
+            scale(i128::MAX / 10, 0, 2, RoundingMode::Floor),
+            Err(OracleAggregatorErrors::ScalingOverflow)
+        );
+    }
+
+    #[test]
+    fn test_mul_div() {
+        let e = Env::default();
+
+        assert_eq!(
+            mul_div(&e, 1_5000000, 1_0000000, 3_0000000, RoundingMode::Floor),
+            Ok(5000000)
+        );
+        assert_eq!(mul_div(&e, 2, 1, 3, RoundingMode::Floor), Ok(0));
+        assert_eq!(mul_div(&e, 2, 1, 3, RoundingMode::Ceil), Ok(1));
+        assert_eq!(mul_div(&e, 2, 1, 3, RoundingMode::HalfEven), Ok(1));
+        assert_eq!(mul_div(&e, 3, 1, 2, RoundingMode::HalfEven), Ok(2));
+        assert_eq!(mul_div(&e, 5, 1, 2, RoundingMode::HalfEven), Ok(2));
+
+        // the intermediate product overflows an i128
+        let large = i128::MAX / 10;
+        assert_eq!(
+            mul_div(&e, large, 100, 1_000, RoundingMode::Floor),
+            Ok(large / 10)
+        );
+        assert_eq!(
+            mul_div(&e, large, 100, 1_000, RoundingMode::Ceil),
+            Ok(large / 10 + 1)
+        );
+
+        // the result overflows an i128
+        assert_eq!(
+            mul_div(&e, i128::MAX, 2, 1, RoundingMode::Floor),
+            Err(OracleAggregatorErrors::ScalingOverflow)
+        );
+
+        assert_eq!(
+            mul_div(&e, 1, 1, 0, RoundingMode::Floor),
+            Err(OracleAggregatorErrors::ScalingOverflow)
+        );
+    }
+}
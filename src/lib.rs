@@ -3,11 +3,17 @@
 #[cfg(any(test, feature = "testutils"))]
 extern crate std;
 
+mod config;
 pub mod contract;
 mod errors;
+mod events;
+mod fixed_point;
 mod price_data;
 mod storage;
+mod twap;
 mod types;
+mod upgrade;
+mod usdc;
 
 pub use contract::*;
 #[cfg(any(test, feature = "testutils"))]
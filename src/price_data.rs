@@ -1,53 +1,1210 @@
 use sep_40_oracle::{Asset, PriceData, PriceFeedClient};
-use soroban_sdk::Env;
+use soroban_sdk::{panic_with_error, unwrap::UnwrapOptimized, Address, Env, Map, Vec};
 
-use crate::storage;
+use crate::{
+    config,
+    errors::OracleAggregatorErrors,
+    events::OracleAggregatorEvents,
+    fixed_point::{mul_div, scalar, scale},
+    storage,
+    types::{
+        AssetConfig, GracePrice, OracleConfig, PathLeg, PegFeed, PeggedAsset, PriceDetail,
+        PriceMethod, RetryStrategy, RoundingMode, SourcedPrice, UsdcSource,
+    },
+    usdc::{get_usdc_price, get_usdc_price_at, read_usd_to_usdc, usd_to_usdc, usdc_to_usd},
+};
+
+/// The most rounds `get_prices` fetches, so pricing every round stays within the instruction limit
+pub const MAX_RECORDS: u32 = 20;
+
+/// The configs used to aggregate a price, loaded once so they can be shared between assets
+pub struct PriceConfig {
+    /// The configs of the source oracles
+    pub oracle_configs: Vec<OracleConfig>,
+    /// The decimals the aggregator reports prices in
+    pub decimals: u32,
+    /// The rounding applied when a price loses precision
+    pub rounding: RoundingMode,
+    /// The global max age of a price, in seconds
+    pub max_age: u64,
+    /// The address of the USDC asset
+    pub usdc: Address,
+    /// The source of the price of USDC in USD, if it exists
+    pub usdc_source: Option<UsdcSource>,
+}
+
+impl PriceConfig {
+    /// Load the price config from storage
+    pub fn load(e: &Env) -> Self {
+        PriceConfig {
+            oracle_configs: storage::get_oracle_configs(e),
+            decimals: storage::get_decimals(e),
+            rounding: storage::get_rounding(e),
+            max_age: storage::get_max_age(e),
+            usdc: storage::get_usdc(e),
+            usdc_source: storage::get_usdc_source(e),
+        }
+    }
+}
+
+/// The source oracle that answered for an aggregated price
+#[derive(Clone)]
+pub struct Answer {
+    /// The address of the source oracle
+    pub oracle_id: Address,
+    /// The number of `resolution` steps back the answer came from. 0 if `lastprice` answered.
+    pub steps: u32,
+}
+
+/// A price aggregated in USDC, along with the source oracle that answered for it
+pub struct AggregatedPrice {
+    /// The price of the asset, along with the oracles that reported it
+    pub sourced: SourcedPrice,
+    /// The source oracle that answered, or None if the asset is priced at a fixed price
+    pub answer: Option<Answer>,
+    /// The number of source oracles whose prices were aggregated
+    pub agreed: u32,
+}
 
 /// Fetch a price based on the asset config
+///
+/// ### Panics
+/// If the asset does not have an asset config
 pub fn get_price(e: &Env, asset: &Asset) -> Option<PriceData> {
-    let config = storage::get_oracle_config(e);
+    get_sourced_price(e, asset).map(|sourced_price| sourced_price.price)
+}
+
+/// Fetch a price based on the asset config, along with the oracles that reported it
+///
+/// If a USDC source is configured, the price is converted to USD, and None is returned if the
+/// USDC source does not have a recent price.
+///
+/// ### Panics
+/// If the asset does not have an asset config
+pub fn get_sourced_price(e: &Env, asset: &Asset) -> Option<SourcedPrice> {
+    get_aggregated_price(e, asset).map(|aggregated_price| aggregated_price.sourced)
+}
+
+/// Fetch a price based on the asset config, along with how it was resolved
+///
+/// The price is the same as the price returned by `get_price`. The answer is:
+/// * the source oracle that reported the median price, or the upper of the two middle prices if
+///   there is an even number of prices
+/// * the fallback oracle that reported the price
+/// * the oracle of the leg of the path that went the most steps back
+/// * the feed of a pegged asset, or None if the pegged asset does not have a feed
+///
+/// ### Panics
+/// If the asset does not have an asset config
+pub fn get_price_detail(e: &Env, asset: &Asset) -> Option<PriceDetail> {
+    let aggregated_price = get_aggregated_price(e, asset)?;
+    Some(to_price_detail(
+        e,
+        aggregated_price.sourced.price,
+        aggregated_price.answer,
+        aggregated_price.agreed,
+    ))
+}
+
+/// Fetch the most recent price of USDC, along with how it was resolved
+///
+/// If a USDC source is not configured, USDC is priced at 1, and there is no answer.
+pub fn get_usdc_price_detail(e: &Env) -> Option<PriceDetail> {
+    match storage::get_usdc_source(e) {
+        Some(source) => {
+            let oldest_timestamp = e.ledger().timestamp() - storage::get_max_age(e);
+            let (price, steps) =
+                fetch_source_price(e, &source.oracle, &source.asset, oldest_timestamp)?;
+            let price = normalize_source_price(
+                price,
+                &storage::get_decimals(e),
+                &source.oracle.decimals,
+                storage::get_rounding(e),
+            )?;
+            let answer = Answer {
+                oracle_id: source.oracle.oracle_id,
+                steps,
+            };
+            Some(to_price_detail(e, price, Some(answer), 1))
+        }
+        None => Some(to_price_detail(
+            e,
+            get_usdc_price(e, storage::get_rounding(e))?,
+            None,
+            0,
+        )),
+    }
+}
+
+/// Build the detail of a price from the answer it was resolved from
+fn to_price_detail(e: &Env, price: PriceData, answer: Option<Answer>, agreed: u32) -> PriceDetail {
+    let (oracle_id, steps) = match answer {
+        Some(answer) => (Some(answer.oracle_id), answer.steps),
+        None => (None, 0),
+    };
+    PriceDetail {
+        age: e.ledger().timestamp().saturating_sub(price.timestamp),
+        price,
+        oracle_id,
+        // an older round was found with the retry strategy in use
+        method: match (steps, storage::get_retry_strategy(e)) {
+            (0, _) => PriceMethod::LastPrice,
+            (_, RetryStrategy::Price) => PriceMethod::Price,
+            (_, RetryStrategy::Prices) => PriceMethod::Prices,
+        },
+        steps,
+        agreed,
+    }
+}
+
+/// Fetch a price based on the asset config, converted to USD if a USDC source is configured,
+/// along with the source oracle that answered for it
+///
+/// ### Panics
+/// If the asset does not have an asset config
+fn get_aggregated_price(e: &Env, asset: &Asset) -> Option<AggregatedPrice> {
+    let price_config = PriceConfig::load(e);
+    let aggregated_price = aggregate_usd_price(e, &price_config, asset)?;
+    cache_price(e, asset, &aggregated_price.sourced.price);
+    Some(aggregated_price)
+}
+
+/// Fetch a price based on the asset config, rounded with `rounding` instead of the configured
+/// rounding
+///
+/// The price is checked against the price bounds of the asset, but is not cached, so the last
+/// known good price does not depend on the rounding requested by a caller.
+///
+/// ### Panics
+/// If the asset does not have an asset config
+pub fn get_rounded_price(e: &Env, asset: &Asset, rounding: RoundingMode) -> Option<PriceData> {
+    let mut price_config = PriceConfig::load(e);
+    price_config.rounding = rounding;
+    aggregate_usd_price(e, &price_config, asset)
+        .map(|aggregated_price| aggregated_price.sourced.price)
+}
+
+/// Aggregate a price based on the asset config, converted to USD if a USDC source is configured,
+/// and check it against the price bounds of the asset
+///
+/// ### Panics
+/// If the asset does not have an asset config
+/// * `PriceOutOfBounds` - The price is not positive, or is outside the price bounds of the asset
+fn aggregate_usd_price(
+    e: &Env,
+    price_config: &PriceConfig,
+    asset: &Asset,
+) -> Option<AggregatedPrice> {
+    let mut aggregated_price = aggregate_asset_price(e, price_config, asset)?;
+    let sourced_price = &mut aggregated_price.sourced;
+    if let Some(usdc_source) = &price_config.usdc_source {
+        // the price is reported in USDC, convert it to USD
+        let usdc_price = get_usdc_price(e, price_config.rounding)?;
+        sourced_price.price =
+            usdc_to_usd(e, &sourced_price.price, &usdc_price, price_config.rounding);
+        sourced_price
+            .sources
+            .push_back(usdc_source.oracle.oracle_id.clone());
+    }
+    check_price_bounds(e, asset, &sourced_price.price);
+    Some(aggregated_price)
+}
+
+/// Fetch the most recent price of an asset, or its last known good price if the price is
+/// older than `max_age` but within the grace period after it
+///
+/// The price of USDC is not cached, so it is only returned if it is at most `max_age` old.
+///
+/// ### Panics
+/// If the asset does not have an asset config
+pub fn get_grace_price(e: &Env, asset: &Asset) -> Option<GracePrice> {
+    if let Some(price) = get_price(e, asset) {
+        return Some(GracePrice {
+            price,
+            stale: false,
+        });
+    }
+    let cached_price = storage::get_last_price(e, asset)?;
+    let max_age = get_asset_max_age(e, asset) + storage::get_grace_period(e);
+    if cached_price.timestamp + max_age < e.ledger().timestamp() {
+        return None;
+    }
+    Some(GracePrice {
+        price: cached_price,
+        stale: true,
+    })
+}
+
+/// Check that a price is positive and within the price bounds of the asset, if it has any
+///
+/// ### Panics
+/// * `PriceOutOfBounds` - The price is not positive, or is outside the price bounds of the asset
+fn check_price_bounds(e: &Env, asset: &Asset, price: &PriceData) {
+    let in_bounds = price.price > 0
+        && match storage::get_price_bounds(e, asset) {
+            Some(bounds) => bounds.min <= price.price && price.price <= bounds.max,
+            None => true,
+        };
+    if !in_bounds {
+        panic_with_error!(e, OracleAggregatorErrors::PriceOutOfBounds);
+    }
+}
+
+/// Write a price into the cache of last known good prices, if it is newer than the cached price
+///
+/// The cache is only served by `lastprice_with_grace` after `max_age`, so nothing is read or
+/// written while the grace period is 0.
+fn cache_price(e: &Env, asset: &Asset, price: &PriceData) {
+    if storage::get_grace_period(e) == 0 {
+        return;
+    }
+    let is_newer = match storage::get_last_price(e, asset) {
+        Some(cached_price) => cached_price.timestamp < price.timestamp,
+        None => true,
+    };
+    if is_newer {
+        storage::set_last_price(e, asset, price);
+    }
+}
+
+/// Fetch a price in USDC based on the asset config, without converting it to USD
+///
+/// The price is checked against the price bounds of the asset like every other price, so if a
+/// USDC source is configured, it is checked after it is converted to USD, and None is returned if
+/// the USDC source does not have a recent price.
+///
+/// ### Panics
+/// If the asset does not have an asset config
+/// * `PriceOutOfBounds` - The price is not positive, or is outside the price bounds of the asset
+pub fn get_usdc_denominated_price(e: &Env, asset: &Asset) -> Option<PriceData> {
+    let price_config = PriceConfig::load(e);
+    let price = aggregate_asset_price(e, &price_config, asset)?
+        .sourced
+        .price;
+    match price_config.usdc_source {
+        Some(_) => {
+            let usdc_price = get_usdc_price(e, price_config.rounding)?;
+            let usd_price = usdc_to_usd(e, &price, &usdc_price, price_config.rounding);
+            check_price_bounds(e, asset, &usd_price);
+        }
+        None => check_price_bounds(e, asset, &price),
+    }
+    Some(price)
+}
+
+/// Fetch the most recent price of each asset, in the same order as `assets`
+///
+/// The price config is loaded once and shared between the assets, and the price of USDC is
+/// fetched at most once. Paused assets are not priced.
+///
+/// ### Panics
+/// If an asset other than USDC does not have an asset config
+pub fn get_last_prices(e: &Env, assets: &Vec<Asset>) -> Vec<Option<PriceData>> {
+    let price_config = PriceConfig::load(e);
+    let mut usdc_price: Option<Option<PriceData>> = None;
+    let mut prices = Vec::new(e);
+    for asset in assets.iter() {
+        let asset = config::resolve_asset(e, &asset);
+        if config::is_paused(e, &asset) {
+            prices.push_back(None);
+            continue;
+        }
+        let price = match &asset {
+            Asset::Stellar(address) if *address == price_config.usdc => usdc_price
+                .get_or_insert_with(|| get_usdc_price(e, price_config.rounding))
+                .clone(),
+            _ => aggregate_asset_price(e, &price_config, &asset)
+                .and_then(|aggregated_price| {
+                    let price = aggregated_price.sourced.price;
+                    if price_config.usdc_source.is_none() {
+                        return Some(price);
+                    }
+                    // the price is reported in USDC, convert it to USD
+                    let usdc_price = usdc_price
+                        .get_or_insert_with(|| get_usdc_price(e, price_config.rounding))
+                        .clone()?;
+                    Some(usdc_to_usd(e, &price, &usdc_price, price_config.rounding))
+                })
+                .inspect(|price| {
+                    check_price_bounds(e, &asset, price);
+                    cache_price(e, &asset, price);
+                }),
+        };
+        prices.push_back(price);
+    }
+    prices
+}
+
+/// Derive the price of a base asset in a quote asset from the price of each asset in USDC
+///
+/// The cross price uses the aggregator's decimals and the older of the two timestamps. Returns
+/// None if the quote price is not positive, or the cross price does not fit in an i128.
+pub fn cross_price(e: &Env, base_price: &PriceData, quote_price: &PriceData) -> Option<PriceData> {
+    let scalar = scalar(storage::get_decimals(e)).ok()?;
+    let price = mul_div(
+        e,
+        base_price.price,
+        scalar,
+        quote_price.price,
+        storage::get_rounding(e),
+    )
+    .ok()?;
+    Some(PriceData {
+        price,
+        timestamp: base_price.timestamp.min(quote_price.timestamp),
+    })
+}
+
+/// Aggregate a price in USDC based on the asset config, from the price of the underlying asset
+/// if the asset is derived, at the fixed price if the asset is pegged, or through the path of
+/// oracles of the asset
+///
+/// ### Panics
+/// If the asset is not derived, pegged or a path asset, and does not have an asset config
+fn aggregate_asset_price(
+    e: &Env,
+    price_config: &PriceConfig,
+    asset: &Asset,
+) -> Option<AggregatedPrice> {
+    if let Some(pegged) = storage::get_pegged_assets(e).get(asset.clone()) {
+        return get_pegged_price(e, price_config, asset, &pegged);
+    }
+    if let Some(legs) = storage::get_asset_path(e, asset) {
+        return get_path_price(e, price_config, &legs);
+    }
+    match storage::get_derived_asset(e, asset) {
+        Some(derived) => {
+            let mut aggregated_price = aggregate_price(e, price_config, &derived.underlying)?;
+            aggregated_price.sourced.price = apply_ratio(
+                e,
+                &aggregated_price.sourced.price,
+                derived.ratio,
+                price_config.rounding,
+            );
+            Some(aggregated_price)
+        }
+        None => aggregate_price(e, price_config, asset),
+    }
+}
+
+/// Fetch the price of an asset in USDC through a path of oracles
+///
+/// Each leg must have a price at most `max_age` old. The price of each leg is normalized to the
+/// aggregator's decimals and multiplied with the next, and the result uses the oldest timestamp
+/// of the legs.
+fn get_path_price(
+    e: &Env,
+    price_config: &PriceConfig,
+    legs: &Vec<PathLeg>,
+) -> Option<AggregatedPrice> {
+    let oldest_timestamp = e.ledger().timestamp() - price_config.max_age;
+    let mut price: Option<PriceData> = None;
+    let mut sources = Vec::new(e);
+    let mut answer: Option<Answer> = None;
+    for leg in legs.iter() {
+        let (leg_price, steps) = fetch_source_price(e, &leg.oracle, &leg.asset, oldest_timestamp)?;
+        if answer.as_ref().is_none_or(|answer| answer.steps < steps) {
+            answer = Some(Answer {
+                oracle_id: leg.oracle.oracle_id.clone(),
+                steps,
+            });
+        }
+        // a leg whose price is not positive or can't be normalized is treated like a leg without
+        // a price
+        let leg_price = normalize_source_price(
+            leg_price,
+            &price_config.decimals,
+            &leg.oracle.decimals,
+            price_config.rounding,
+        )?;
+        price = Some(match price {
+            Some(price) => multiply_prices(
+                e,
+                &price,
+                &leg_price,
+                price_config.decimals,
+                price_config.rounding,
+            ),
+            None => leg_price,
+        });
+        sources.push_back(leg.oracle.oracle_id);
+    }
+    Some(AggregatedPrice {
+        sourced: SourcedPrice {
+            price: price?,
+            sources,
+            fallback: None,
+        },
+        answer,
+        agreed: 1,
+    })
+}
+
+/// Fetch the price of an asset in USDC through a path of oracles at a specific timestamp
+///
+/// Returns None if the oracle of any leg did not report a price for the round containing the
+/// timestamp.
+fn get_path_price_at(
+    e: &Env,
+    reader: &mut RoundReader,
+    legs: &Vec<PathLeg>,
+    timestamp: u64,
+) -> Option<PriceData> {
+    let decimals = storage::get_decimals(e);
+    let rounding = storage::get_rounding(e);
+    let mut price: Option<PriceData> = None;
+    for leg in legs.iter() {
+        let leg_price = reader.price(e, &leg.oracle, &leg.asset, timestamp)?;
+        let leg_price =
+            normalize_source_price(leg_price, &decimals, &leg.oracle.decimals, rounding)?;
+        price = Some(match price {
+            Some(price) => multiply_prices(e, &price, &leg_price, decimals, rounding),
+            None => leg_price,
+        });
+    }
+    price
+}
+
+/// Multiply two prices in the aggregator's decimals, using the older of the two timestamps
+///
+/// ### Panics
+/// * `ScalingOverflow` - The product does not fit in an i128
+fn multiply_prices(
+    e: &Env,
+    price: &PriceData,
+    other_price: &PriceData,
+    decimals: u32,
+    rounding: RoundingMode,
+) -> PriceData {
+    let product = scalar(decimals)
+        .and_then(|scalar| mul_div(e, price.price, other_price.price, scalar, rounding))
+        .unwrap_or_else(|error| panic_with_error!(e, error));
+    PriceData {
+        price: product,
+        timestamp: price.timestamp.min(other_price.timestamp),
+    }
+}
+
+/// Fetch the fixed price of a pegged asset in USDC
+///
+/// If the pegged asset has a feed, the fixed price is only returned if the feed has a price at most
+/// `max_age` old within the tolerance band, and uses the timestamp of that price. Otherwise, the
+/// fixed price uses the current ledger timestamp.
+fn get_pegged_price(
+    e: &Env,
+    price_config: &PriceConfig,
+    asset: &Asset,
+    pegged: &PeggedAsset,
+) -> Option<AggregatedPrice> {
+    let mut sources = Vec::new(e);
+    let mut answer: Option<Answer> = None;
+    let price = match &pegged.feed {
+        PegFeed::Source(feed) => {
+            let config = find_oracle_config(&price_config.oracle_configs, &feed.oracle_id);
+            let oldest_timestamp = e.ledger().timestamp() - price_config.max_age;
+            let (feed_price, steps) =
+                fetch_source_price(e, &config, &feed.asset, oldest_timestamp)?;
+            let feed_price = usd_to_usdc(e, &config, feed_price, price_config.rounding)?;
+            let feed_price = normalize_source_price(
+                feed_price,
+                &price_config.decimals,
+                &config.decimals,
+                price_config.rounding,
+            )?;
+            sources.push_back(feed.oracle_id.clone());
+            answer = Some(Answer {
+                oracle_id: feed.oracle_id.clone(),
+                steps,
+            });
+            check_peg(e, asset, pegged, &feed.oracle_id, &feed_price)?
+        }
+        PegFeed::Unchecked => PriceData {
+            price: pegged.price,
+            timestamp: e.ledger().timestamp(),
+        },
+    };
+    let agreed = sources.len();
+    Some(AggregatedPrice {
+        sourced: SourcedPrice {
+            price,
+            sources,
+            fallback: None,
+        },
+        answer,
+        agreed,
+    })
+}
+
+/// Check a price reported by the feed of a pegged asset against the fixed price
+///
+/// Returns the fixed price at the timestamp of the reported price, or None if the reported price
+/// is outside of the tolerance band. Emits an event if it is.
+fn check_peg(
+    e: &Env,
+    asset: &Asset,
+    pegged: &PeggedAsset,
+    oracle_id: &Address,
+    feed_price: &PriceData,
+) -> Option<PriceData> {
+    let tolerance = pegged.tolerance as i128;
+    if (feed_price.price - pegged.price).abs() * 10_000 > tolerance * pegged.price {
+        OracleAggregatorEvents::price_depeg(
+            e,
+            asset.clone(),
+            oracle_id.clone(),
+            pegged.price,
+            feed_price.price,
+        );
+        return None;
+    }
+    Some(PriceData {
+        price: pegged.price,
+        timestamp: feed_price.timestamp,
+    })
+}
+
+/// Apply the ratio of a derived asset, in basis points, to the price of its underlying asset
+///
+/// ### Panics
+/// * `ScalingOverflow` - The derived price does not fit in an i128
+fn apply_ratio(e: &Env, price: &PriceData, ratio: u32, rounding: RoundingMode) -> PriceData {
+    let derived_price = mul_div(e, price.price, ratio as i128, 10_000, rounding)
+        .unwrap_or_else(|error| panic_with_error!(e, error));
+    PriceData {
+        price: derived_price,
+        timestamp: price.timestamp,
+    }
+}
+
+/// Aggregate a price in USDC based on the asset config
+///
+/// Each source oracle of the asset is queried for a recent price. Sources that do not have a
+/// price at most `max_age` old, whose price moved more than the max deviation of the asset from
+/// the previous round, or whose price is not positive or can't be normalized to the aggregator's
+/// decimals, are dropped, and the median of the remaining prices is returned.
+/// If every source is dropped, the fallback oracles are tried in order, and the first valid
+/// price found is returned.
+///
+/// ### Panics
+/// If the asset does not have an asset config
+fn aggregate_price(e: &Env, price_config: &PriceConfig, asset: &Asset) -> Option<AggregatedPrice> {
+    let asset_config = load_asset_config(e, asset);
+    let configs = &price_config.oracle_configs;
+    let decimals = price_config.decimals;
+    let rounding = price_config.rounding;
+    let max_age = get_max_age(&asset_config, price_config.max_age);
+    let oldest_timestamp = e.ledger().timestamp() - max_age;
+    let max_deviation = storage::get_max_deviation(e, asset);
+    let mut prices: Vec<PriceData> = Vec::new(e);
+    let mut sources: Vec<Address> = Vec::new(e);
+    let mut steps: Vec<u32> = Vec::new(e);
+    for source in asset_config.sources.iter() {
+        let config = find_oracle_config(configs, &source.oracle_id);
+        if let Some((price, source_steps)) =
+            fetch_source_price(e, &config, &source.asset, oldest_timestamp)
+        {
+            if exceeds_max_deviation(e, asset, &config, &source.asset, &price, max_deviation) {
+                continue;
+            }
+            // prices in USD are converted to USDC before they are normalized, and a price that is
+            // not positive or can't be normalized is dropped like a stale price
+            let price = usd_to_usdc(e, &config, price, rounding).and_then(|price| {
+                normalize_source_price(price, &decimals, &config.decimals, rounding)
+            });
+            if let Some(price) = price {
+                prices.push_back(price);
+                sources.push_back(source.oracle_id);
+                steps.push_back(source_steps);
+            }
+        }
+    }
+    if let Some(price) = median(e, &prices) {
+        let index = median_index(&prices);
+        let answer = Answer {
+            oracle_id: sources.get_unchecked(index),
+            steps: steps.get_unchecked(index),
+        };
+        return Some(AggregatedPrice {
+            agreed: sources.len(),
+            sourced: SourcedPrice {
+                price,
+                sources,
+                fallback: None,
+            },
+            answer: Some(answer),
+        });
+    }
+
+    // no source oracle had a recent price, attempt the fallback oracles in order
+    for (index, fallback) in asset_config.fallbacks.iter().enumerate() {
+        let config = find_oracle_config(configs, &fallback.oracle_id);
+        if let Some((price, steps)) =
+            fetch_source_price(e, &config, &fallback.asset, oldest_timestamp)
+        {
+            if exceeds_max_deviation(e, asset, &config, &fallback.asset, &price, max_deviation) {
+                continue;
+            }
+            let price = usd_to_usdc(e, &config, price, rounding).and_then(|price| {
+                normalize_source_price(price, &decimals, &config.decimals, rounding)
+            });
+            if let Some(price) = price {
+                OracleAggregatorEvents::price_fallback(
+                    e,
+                    asset.clone(),
+                    fallback.oracle_id.clone(),
+                    index as u32,
+                );
+                return Some(AggregatedPrice {
+                    sourced: SourcedPrice {
+                        price,
+                        sources: Vec::from_array(e, [fallback.oracle_id.clone()]),
+                        fallback: Some(index as u32),
+                    },
+                    answer: Some(Answer {
+                        oracle_id: fallback.oracle_id,
+                        steps,
+                    }),
+                    agreed: 1,
+                });
+            }
+        }
+    }
+    OracleAggregatorEvents::price_none(e, asset.clone());
+    None
+}
+
+/// Fetch the most recent price from a source oracle that is not older than `oldest_timestamp`
+pub fn get_source_price(
+    e: &Env,
+    config: &OracleConfig,
+    asset: &Asset,
+    oldest_timestamp: u64,
+) -> Option<PriceData> {
+    fetch_source_price(e, config, asset, oldest_timestamp).map(|(price, _)| price)
+}
+
+/// Fetch the most recent price from a source oracle that is not older than `oldest_timestamp`,
+/// along with the number of `resolution` steps back the price was found
+///
+/// The number of steps is 0 if `lastprice` returned the price.
+fn fetch_source_price(
+    e: &Env,
+    config: &OracleConfig,
+    asset: &Asset,
+    oldest_timestamp: u64,
+) -> Option<(PriceData, u32)> {
     let oracle = PriceFeedClient::new(e, &config.oracle_id);
     let mut price: Option<PriceData> = oracle.lastprice(asset);
-    let decimals = storage::get_decimals(e);
-    let oldest_timestamp = e.ledger().timestamp() - storage::get_max_age(e);
+    let mut steps: u32 = 0;
     if price.is_none() {
-        let mut next_timestamp = e.ledger().timestamp() - config.resolution as u64;
-        // attempt to use the `price` method to get an older price if price is None
-        while price.is_none() && next_timestamp >= oldest_timestamp {
-            price = oracle.price(asset, &next_timestamp);
-            next_timestamp -= config.resolution as u64;
-        }
+        // attempt to find an older price with the retry strategy if price is None
+        (price, steps) = match storage::get_retry_strategy(e) {
+            RetryStrategy::Price => retry_with_price(e, &oracle, config, asset, oldest_timestamp),
+            RetryStrategy::Prices => retry_with_prices(e, &oracle, config, asset, oldest_timestamp),
+        };
     }
-    // if we found a price, normalize it and verify it is not too old
+    // if we found a price, verify it is not too old
     // otherwise, return None
-    if let Some(price) = price {
-        let normalized_price = normalize_price(price, &decimals, &config.decimals);
-        if normalized_price.timestamp >= oldest_timestamp {
-            return Some(normalized_price);
+    match price {
+        Some(price) if price.timestamp >= oldest_timestamp => {
+            if steps > 0 {
+                OracleAggregatorEvents::price_retry(
+                    e,
+                    asset.clone(),
+                    config.oracle_id.clone(),
+                    steps,
+                    price.timestamp,
+                );
+            }
+            Some((price, steps))
+        }
+        _ => {
+            OracleAggregatorEvents::price_stale(
+                e,
+                asset.clone(),
+                config.oracle_id.clone(),
+                price.map(|price| price.timestamp),
+            );
+            None
         }
     }
-    return None;
 }
 
-/// Normalize the price data to the correct number of decimals
-fn normalize_price(mut price_data: PriceData, decimals: &u32, oracle_decimals: &u32) -> PriceData {
-    if oracle_decimals > decimals {
-        let diff = oracle_decimals - decimals;
-        price_data.price = price_data.price / 10_i128.pow(diff);
-        return price_data;
-    } else if oracle_decimals < decimals {
-        let diff = decimals - oracle_decimals;
-        price_data.price = price_data.price * 10_i128.pow(diff);
-        return price_data;
+/// Find an older price by calling `price` once per `resolution` step back, until a price is found
+/// or the step is older than `oldest_timestamp`
+///
+/// Returns the price, if one was found, and the number of steps taken.
+fn retry_with_price(
+    e: &Env,
+    oracle: &PriceFeedClient,
+    config: &OracleConfig,
+    asset: &Asset,
+    oldest_timestamp: u64,
+) -> (Option<PriceData>, u32) {
+    let mut price: Option<PriceData> = None;
+    let mut steps: u32 = 0;
+    let mut next_timestamp = e.ledger().timestamp() - config.resolution as u64;
+    while price.is_none() && next_timestamp >= oldest_timestamp {
+        price = oracle.price(asset, &next_timestamp);
+        next_timestamp -= config.resolution as u64;
+        steps += 1;
+    }
+    (price, steps)
+}
+
+/// Find an older price by fetching the rounds the `price` retry would query with a single
+/// `prices` call, and using the newest round returned
+///
+/// Returns the price, if one was found, and the number of `resolution` steps back it is from.
+fn retry_with_prices(
+    e: &Env,
+    oracle: &PriceFeedClient,
+    config: &OracleConfig,
+    asset: &Asset,
+    oldest_timestamp: u64,
+) -> (Option<PriceData>, u32) {
+    let resolution = config.resolution as u64;
+    let timestamp = e.ledger().timestamp();
+    let max_steps = ((timestamp - oldest_timestamp) / resolution) as u32;
+    if max_steps == 0 {
+        return (None, 0);
+    }
+    // the most recent round is included, as `prices` starts from it
+    let newest_price = oracle
+        .prices(asset, &(max_steps + 1))
+        .and_then(|prices| prices.iter().max_by_key(|price| price.timestamp));
+    match newest_price {
+        Some(price) => {
+            // `lastprice` did not return a price, so the price is at least one step back
+            let steps = (timestamp.saturating_sub(price.timestamp) / resolution) as u32;
+            (Some(price), steps.max(1))
+        }
+        None => (None, 0),
+    }
+}
+
+/// Check if a price from a source oracle moved more than `max_deviation` from the previous round
+/// reported by the same oracle. Emits an event if it did.
+///
+/// Returns false if there is no max deviation, or the source oracle did not report the previous round.
+///
+/// ### Arguments
+/// * `asset` - The asset being priced
+/// * `config` - The config of the source oracle
+/// * `source_asset` - The asset identifier used by the source oracle
+/// * `price` - The price reported by the source oracle
+/// * `max_deviation` - The max deviation between consecutive rounds, in basis points
+fn exceeds_max_deviation(
+    e: &Env,
+    asset: &Asset,
+    config: &OracleConfig,
+    source_asset: &Asset,
+    price: &PriceData,
+    max_deviation: Option<u32>,
+) -> bool {
+    let max_deviation = match max_deviation {
+        Some(max_deviation) => max_deviation as i128,
+        None => return false,
+    };
+    let oracle = PriceFeedClient::new(e, &config.oracle_id);
+    let previous_timestamp = price.timestamp - config.resolution as u64;
+    let previous_price = match oracle.price(source_asset, &previous_timestamp) {
+        Some(previous_price) => previous_price.price,
+        None => return false,
+    };
+    let exceeded = if previous_price > 0 {
+        (price.price - previous_price).abs() * 10_000 > max_deviation * previous_price
     } else {
-        return price_data;
+        price.price != previous_price
+    };
+    if exceeded {
+        OracleAggregatorEvents::price_deviation(
+            e,
+            asset.clone(),
+            config.oracle_id.clone(),
+            previous_price,
+            price.price,
+        );
+    }
+    exceeded
+}
+
+/// Fetch the price of an asset at a specific timestamp
+///
+/// Each source oracle of the asset is queried for the round containing the timestamp, and the
+/// median of the reported prices is returned. If no source oracle reported a price, the fallback
+/// oracles are tried in order. Returns None if no oracle reported a price.
+///
+/// If a USDC source is configured, the price is converted to USD with the price of USDC at the
+/// same timestamp.
+///
+/// ### Panics
+/// If the asset does not have an asset config
+pub fn get_price_at(e: &Env, asset: &Asset, timestamp: u64) -> Option<PriceData> {
+    read_price_at(e, &mut RoundReader::new(e), asset, timestamp)
+}
+
+/// Fetch the price of an asset at a specific timestamp like `get_price_at`, reading the rounds
+/// reported by oracles with `reader`
+fn read_price_at(
+    e: &Env,
+    reader: &mut RoundReader,
+    asset: &Asset,
+    timestamp: u64,
+) -> Option<PriceData> {
+    let mut price = aggregate_price_at(e, reader, asset, timestamp)?;
+    if storage::get_usdc_source(e).is_some() {
+        let usdc_price = get_usdc_price_at(e, reader, timestamp)?;
+        price = usdc_to_usd(e, &price, &usdc_price, storage::get_rounding(e));
+    }
+    check_price_bounds(e, asset, &price);
+    Some(price)
+}
+
+/// Aggregate a price in USDC at a specific timestamp based on the asset config, from the price of
+/// the underlying asset if the asset is derived, at the fixed price if the asset is pegged, or
+/// through the path of oracles of the asset
+fn aggregate_price_at(
+    e: &Env,
+    reader: &mut RoundReader,
+    asset: &Asset,
+    timestamp: u64,
+) -> Option<PriceData> {
+    if let Some(legs) = storage::get_asset_path(e, asset) {
+        return get_path_price_at(e, reader, &legs, timestamp);
+    }
+    if let Some(pegged) = storage::get_pegged_assets(e).get(asset.clone()) {
+        let feed = match &pegged.feed {
+            PegFeed::Source(feed) => feed,
+            PegFeed::Unchecked => {
+                return Some(PriceData {
+                    price: pegged.price,
+                    timestamp,
+                })
+            }
+        };
+        let config = find_oracle_config(&storage::get_oracle_configs(e), &feed.oracle_id);
+        let rounding = storage::get_rounding(e);
+        let feed_price = reader.price(e, &config, &feed.asset, timestamp)?;
+        let feed_price = read_usd_to_usdc(e, reader, &config, feed_price, rounding)?;
+        let feed_price = normalize_source_price(
+            feed_price,
+            &storage::get_decimals(e),
+            &config.decimals,
+            rounding,
+        )?;
+        return check_peg(e, asset, &pegged, &feed.oracle_id, &feed_price);
+    }
+    if let Some(derived) = storage::get_derived_asset(e, asset) {
+        let price = aggregate_price_at(e, reader, &derived.underlying, timestamp)?;
+        return Some(apply_ratio(
+            e,
+            &price,
+            derived.ratio,
+            storage::get_rounding(e),
+        ));
+    }
+    let asset_config = load_asset_config(e, asset);
+    let configs = storage::get_oracle_configs(e);
+    let decimals = storage::get_decimals(e);
+    let rounding = storage::get_rounding(e);
+    let mut prices: Vec<PriceData> = Vec::new(e);
+    for source in asset_config.sources.iter() {
+        let config = find_oracle_config(&configs, &source.oracle_id);
+        let price = reader
+            .price(e, &config, &source.asset, timestamp)
+            .and_then(|price| read_usd_to_usdc(e, reader, &config, price, rounding))
+            .and_then(|price| normalize_source_price(price, &decimals, &config.decimals, rounding));
+        if let Some(price) = price {
+            prices.push_back(price);
+        }
     }
+    if prices.is_empty() {
+        for fallback in asset_config.fallbacks.iter() {
+            let config = find_oracle_config(&configs, &fallback.oracle_id);
+            let price = reader
+                .price(e, &config, &fallback.asset, timestamp)
+                .and_then(|price| read_usd_to_usdc(e, reader, &config, price, rounding))
+                .and_then(|price| {
+                    normalize_source_price(price, &decimals, &config.decimals, rounding)
+                });
+            if price.is_some() {
+                return price;
+            }
+        }
+    }
+    median(e, &prices)
+}
+
+/// Fetch up to `records` of the most recent prices of an asset, one for each `resolution` step
+/// back from the most recent round
+///
+/// Each round is priced with the same logic as `get_price_at`, so it is the median of the source
+/// oracles that reported the round, or the first fallback oracle that reported it if no source
+/// oracle did. The rounds are read from each oracle with a single `prices` call. Rounds without a
+/// price are skipped, not returned as gaps, so the result can contain fewer than `records`
+/// entries. At most `MAX_RECORDS` rounds are fetched. Returns None if no round has a price.
+///
+/// ### Panics
+/// If the asset is not derived, pegged or a path asset, and does not have an asset config
+pub fn get_prices(e: &Env, asset: &Asset, records: u32) -> Option<Vec<PriceData>> {
+    let records = records.min(MAX_RECORDS);
+    if records == 0 {
+        return None;
+    }
+    let resolution = config::get_resolution(e) as u64;
+    let latest = e.ledger().timestamp() / resolution * resolution;
+    let window = RoundWindow {
+        latest,
+        oldest: latest.saturating_sub((records - 1) as u64 * resolution),
+        resolution,
+    };
+    let mut reader = RoundReader::with_window(e, window);
+    let mut timestamp = latest;
+    let mut prices = Vec::new(e);
+    for _ in 0..records {
+        if let Some(price) = read_price_at(e, &mut reader, asset, timestamp) {
+            prices.push_back(price);
+        }
+        timestamp = match timestamp.checked_sub(resolution) {
+            Some(timestamp) => timestamp,
+            None => break,
+        };
+    }
+    if prices.is_empty() {
+        return None;
+    }
+    Some(prices)
+}
+
+/// The rounds priced by `get_prices`, one for each `resolution` step from the latest round back to
+/// the oldest round
+pub struct RoundWindow {
+    /// The timestamp of the latest round
+    latest: u64,
+    /// The timestamp of the oldest round
+    oldest: u64,
+    /// The time between rounds, in seconds
+    resolution: u64,
+}
+
+impl RoundWindow {
+    /// Check if a timestamp is one of the rounds of the window
+    fn contains(&self, timestamp: u64) -> bool {
+        timestamp >= self.oldest
+            && timestamp <= self.latest
+            && (self.latest - timestamp).is_multiple_of(self.resolution)
+    }
+}
+
+/// Reads the prices oracles reported for past rounds
+///
+/// Without a window, each round is read from the oracle with `price`. With a window, the rounds of
+/// the window are read from each oracle with a single `prices` call the first time the oracle is
+/// read for an asset, and served from memory after that. Rounds older than the oldest round
+/// returned by `prices` are still read with `price`, as an oracle can stop at a missing round.
+pub struct RoundReader {
+    window: Option<RoundWindow>,
+    /// The oldest round returned by each oracle for each asset, and the rounds of the window it
+    /// returned keyed by timestamp
+    history: Map<(Address, Asset), (u64, Map<u64, PriceData>)>,
+}
+
+impl RoundReader {
+    /// A reader that reads each round from the oracle
+    pub fn new(e: &Env) -> Self {
+        RoundReader {
+            window: None,
+            history: Map::new(e),
+        }
+    }
+
+    /// A reader that reads the rounds of `window` from each oracle at once
+    pub fn with_window(e: &Env, window: RoundWindow) -> Self {
+        RoundReader {
+            window: Some(window),
+            history: Map::new(e),
+        }
+    }
+
+    /// Read the price an oracle reported for an asset in the round containing the timestamp
+    pub fn price(
+        &mut self,
+        e: &Env,
+        oracle: &OracleConfig,
+        asset: &Asset,
+        timestamp: u64,
+    ) -> Option<PriceData> {
+        let oracle_client = PriceFeedClient::new(e, &oracle.oracle_id);
+        let window = match &self.window {
+            Some(window) if window.contains(timestamp) => window,
+            _ => return oracle_client.price(asset, &timestamp),
+        };
+        let key = (oracle.oracle_id.clone(), asset.clone());
+        let (oldest_returned, rounds) = match self.history.get(key.clone()) {
+            Some(history) => history,
+            None => {
+                // enough records to reach the oldest round from the most recent round the oracle
+                // could have reported
+                let records =
+                    (e.ledger().timestamp() - window.oldest) / oracle.resolution as u64 + 1;
+                let prices = oracle_client
+                    .prices(asset, &(records as u32))
+                    .unwrap_or_else(|| Vec::new(e));
+                let mut oldest_returned = u64::MAX;
+                let mut rounds = Map::new(e);
+                for price in prices.iter() {
+                    oldest_returned = oldest_returned.min(price.timestamp);
+                    if window.contains(price.timestamp) {
+                        rounds.set(price.timestamp, price);
+                    }
+                }
+                let history = (oldest_returned, rounds);
+                self.history.set(key, history.clone());
+                history
+            }
+        };
+        if timestamp < oldest_returned {
+            return oracle_client.price(asset, &timestamp);
+        }
+        rounds.get(timestamp)
+    }
+}
+
+/// Fetch the max age of a price of an asset, in seconds
+///
+/// A derived asset uses the max age of its underlying asset, and pegged and path assets use the
+/// global max age.
+///
+/// ### Panics
+/// If the asset is not derived, pegged or a path asset, and does not have an asset config
+pub fn get_asset_max_age(e: &Env, asset: &Asset) -> u64 {
+    if storage::get_pegged_assets(e).contains_key(asset.clone())
+        || storage::get_asset_path(e, asset).is_some()
+    {
+        return storage::get_max_age(e);
+    }
+    let asset_config = match storage::get_derived_asset(e, asset) {
+        Some(derived) => load_asset_config(e, &derived.underlying),
+        None => load_asset_config(e, asset),
+    };
+    get_max_age(&asset_config, storage::get_max_age(e))
+}
+
+/// The max age of an asset config, or the global max age if it does not set one
+fn get_max_age(asset_config: &AssetConfig, global_max_age: u64) -> u64 {
+    asset_config.max_age.unwrap_or(global_max_age)
+}
+
+/// Load the asset config for an asset
+///
+/// ### Panics
+/// If the asset does not have an asset config
+fn load_asset_config(e: &Env, asset: &Asset) -> AssetConfig {
+    match storage::get_asset_config(e, asset) {
+        Some(config) => config,
+        None => panic_with_error!(e, OracleAggregatorErrors::InvalidAssetOracle),
+    }
+}
+
+/// Find the config of a source oracle. Asset configs are validated to only use
+/// source oracles that have a config, including for fallbacks.
+fn find_oracle_config(configs: &Vec<OracleConfig>, oracle_id: &Address) -> OracleConfig {
+    configs
+        .iter()
+        .find(|config| config.oracle_id == *oracle_id)
+        .unwrap_optimized()
+}
+
+/// Calculate the median of a set of prices
+///
+/// If there is an even number of prices, the average of the two middle prices is used along with
+/// the older of their timestamps. Returns None if there are no prices.
+fn median(e: &Env, prices: &Vec<PriceData>) -> Option<PriceData> {
+    let mut sorted: Vec<PriceData> = Vec::new(e);
+    for price in prices.iter() {
+        let index = sorted
+            .iter()
+            .position(|sorted_price| price.price < sorted_price.price)
+            .unwrap_or(sorted.len() as usize);
+        sorted.insert(index as u32, price);
+    }
+
+    let len = sorted.len();
+    if len == 0 {
+        return None;
+    }
+    let upper = sorted.get_unchecked(len / 2);
+    if len % 2 == 1 {
+        return Some(upper);
+    }
+    let lower = sorted.get_unchecked(len / 2 - 1);
+    Some(PriceData {
+        price: lower.price + (upper.price - lower.price) / 2,
+        timestamp: lower.timestamp.min(upper.timestamp),
+    })
+}
+
+/// Find the index of the price used as the median of a set of prices, or the upper of the two
+/// middle prices if there is an even number of prices
+///
+/// Requires at least one price.
+fn median_index(prices: &Vec<PriceData>) -> u32 {
+    let len = prices.len();
+    for (index, price) in prices.iter().enumerate() {
+        let mut lower = 0;
+        let mut equal_before = 0;
+        for (other_index, other_price) in prices.iter().enumerate() {
+            if other_price.price < price.price {
+                lower += 1;
+            } else if other_price.price == price.price && other_index < index {
+                equal_before += 1;
+            }
+        }
+        // the position of the price once sorted, with equal prices kept in order
+        if lower + equal_before == len / 2 {
+            return index as u32;
+        }
+    }
+    len / 2
+}
+
+/// Normalize a price reported by a source oracle to the aggregator's decimals
+///
+/// Returns None if the normalized price is not positive, or does not fit in an i128, so the
+/// source is dropped like a source without a recent price.
+pub fn normalize_source_price(
+    price_data: PriceData,
+    decimals: &u32,
+    oracle_decimals: &u32,
+    rounding: RoundingMode,
+) -> Option<PriceData> {
+    normalize_price(price_data, decimals, oracle_decimals, rounding)
+        .ok()
+        .filter(|price_data| price_data.price > 0)
+}
+
+/// Normalize the price data to the correct number of decimals
+///
+/// If decimals are removed, the price is rounded with `rounding`.
+///
+/// ### Errors
+/// * `ScalingOverflow` - The normalized price does not fit in an i128
+pub fn normalize_price(
+    mut price_data: PriceData,
+    decimals: &u32,
+    oracle_decimals: &u32,
+    rounding: RoundingMode,
+) -> Result<PriceData, OracleAggregatorErrors> {
+    price_data.price = scale(price_data.price, *oracle_decimals, *decimals, rounding)?;
+    Ok(price_data)
 }
 
 // @dev: `get_price` tested in intergration tests in `test_lastprice.rs`
 #[cfg(test)]
 mod test {
     use super::*;
+    use soroban_sdk::vec;
 
     #[test]
     fn test_normalize_price() {
@@ -55,16 +1212,72 @@ mod test {
             price: 1_2345678,
             timestamp: 100,
         };
-        let normalized_price = normalize_price(price.clone(), &6, &7);
+        let normalized_price = normalize_price(price.clone(), &6, &7, RoundingMode::Floor).unwrap();
         assert_eq!(normalized_price.price, 1234567);
 
-        let normalized_price = normalize_price(price.clone(), &8, &7);
+        let normalized_price = normalize_price(price.clone(), &6, &7, RoundingMode::Ceil).unwrap();
+        assert_eq!(normalized_price.price, 1234568);
+
+        let normalized_price = normalize_price(price.clone(), &8, &7, RoundingMode::Floor).unwrap();
         assert_eq!(normalized_price.price, 123456780);
 
-        let normalized_price = normalize_price(price.clone(), &18, &7);
+        let normalized_price =
+            normalize_price(price.clone(), &18, &7, RoundingMode::Floor).unwrap();
         assert_eq!(normalized_price.price, 1234567800000000000);
 
-        let normalized_price = normalize_price(price, &2, &7);
+        let normalized_price = normalize_price(price.clone(), &2, &7, RoundingMode::Floor).unwrap();
+        assert_eq!(normalized_price.price, 123);
+
+        let normalized_price = normalize_price(price, &2, &7, RoundingMode::HalfEven).unwrap();
         assert_eq!(normalized_price.price, 123);
     }
+
+    #[test]
+    fn test_normalize_price_overflow() {
+        let price = PriceData {
+            price: 1_2345678,
+            timestamp: 100,
+        };
+        let result = normalize_price(price, &39, &7, RoundingMode::Floor);
+        assert_eq!(result.err(), Some(OracleAggregatorErrors::ScalingOverflow));
+    }
+
+    #[test]
+    fn test_median() {
+        let e = Env::default();
+        let price = |price: i128, timestamp: u64| PriceData { price, timestamp };
+
+        assert!(median(&e, &Vec::new(&e)).is_none());
+
+        let result = median(&e, &vec![&e, price(1_0000000, 100)]).unwrap();
+        assert_eq!(result.price, 1_0000000);
+        assert_eq!(result.timestamp, 100);
+
+        let result = median(
+            &e,
+            &vec![
+                &e,
+                price(1_0500000, 100),
+                price(9_0000000, 300),
+                price(1_0000000, 200),
+            ],
+        )
+        .unwrap();
+        assert_eq!(result.price, 1_0500000);
+        assert_eq!(result.timestamp, 100);
+
+        let result = median(
+            &e,
+            &vec![
+                &e,
+                price(1_0100000, 300),
+                price(1, 400),
+                price(1_0000000, 200),
+                price(9_0000000, 100),
+            ],
+        )
+        .unwrap();
+        assert_eq!(result.price, 1_0050000);
+        assert_eq!(result.timestamp, 200);
+    }
 }
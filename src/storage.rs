@@ -1,11 +1,29 @@
-use crate::types::OracleConfig;
-use sep_40_oracle::Asset;
-use soroban_sdk::{contracttype, unwrap::UnwrapOptimized, Address, Env, Symbol};
+use crate::types::{
+    AssetConfig, DerivedAsset, OracleConfig, PathLeg, PeggedAsset, PriceBounds, RetryStrategy,
+    RoundingMode, UsdConversionConfig, UsdcSource,
+};
+use sep_40_oracle::{Asset, PriceData};
+use soroban_sdk::{contracttype, unwrap::UnwrapOptimized, Address, Env, Map, Symbol, Vec};
 
-const ORACLE_KEY: &str = "Oracle";
+const SCHEMA_VERSION_KEY: &str = "Schema";
+const ADMIN_KEY: &str = "Admin";
+const GUARDIAN_KEY: &str = "Guardian";
+const PAUSED_KEY: &str = "Paused";
+const PAUSED_ASSETS_KEY: &str = "PausedAssets";
+const ORACLES_KEY: &str = "Oracles";
 const DECIMALS_KEY: &str = "Decimals";
+const ROUNDING_KEY: &str = "Rounding";
+const RETRY_STRATEGY_KEY: &str = "RetryStrategy";
 const MAX_AGE_KEY: &str = "MaxAge";
+const GRACE_PERIOD_KEY: &str = "GracePeriod";
 const USDC_KEY: &str = "USDC";
+const USDC_SOURCE_KEY: &str = "USDCSource";
+const USD_CONVERSION_KEY: &str = "USDConversion";
+const ASSETS_KEY: &str = "Assets";
+const DERIVED_ASSETS_KEY: &str = "DerivedAssets";
+const PEGGED_ASSETS_KEY: &str = "PeggedAssets";
+const PATH_ASSETS_KEY: &str = "PathAssets";
+const MAX_DEVIATIONS_KEY: &str = "MaxDeviations";
 
 const ONE_DAY_LEDGERS: u32 = 17280; // assumes 5 seconds per ledger on average
 const LEDGER_THRESHOLD: u32 = 30 * ONE_DAY_LEDGERS;
@@ -15,6 +33,12 @@ const LEDGER_BUMP: u32 = 31 * ONE_DAY_LEDGERS;
 #[contracttype]
 pub enum AggregatorDataKey {
     Asset(Asset),
+    LastPrice(Asset),
+    PriceBounds(Asset),
+    AliasSymbol(Address),
+    AliasAddress(Symbol),
+    Derived(Asset),
+    Path(Asset),
 }
 
 //********** Storage Utils **********//
@@ -28,6 +52,80 @@ pub fn extend_instance(e: &Env) {
 
 /********** Instance **********/
 
+/// Set the version of the storage schema
+pub fn set_schema_version(e: &Env, version: &u32) {
+    e.storage()
+        .instance()
+        .set::<Symbol, u32>(&Symbol::new(e, SCHEMA_VERSION_KEY), version);
+}
+
+/// Get the version of the storage schema. Storage written before the schema was versioned is version 0.
+pub fn get_schema_version(e: &Env) -> u32 {
+    e.storage()
+        .instance()
+        .get::<Symbol, u32>(&Symbol::new(e, SCHEMA_VERSION_KEY))
+        .unwrap_or(0)
+}
+
+/// Set the admin address
+pub fn set_admin(e: &Env, admin: &Address) {
+    e.storage()
+        .instance()
+        .set::<Symbol, Address>(&Symbol::new(e, ADMIN_KEY), admin);
+}
+
+/// Get the admin address
+pub fn get_admin(e: &Env) -> Address {
+    e.storage()
+        .instance()
+        .get::<Symbol, Address>(&Symbol::new(e, ADMIN_KEY))
+        .unwrap_optimized()
+}
+
+/// Set the guardian address
+pub fn set_guardian(e: &Env, guardian: &Address) {
+    e.storage()
+        .instance()
+        .set::<Symbol, Address>(&Symbol::new(e, GUARDIAN_KEY), guardian);
+}
+
+/// Get the guardian address, if it exists
+pub fn get_guardian(e: &Env) -> Option<Address> {
+    e.storage()
+        .instance()
+        .get::<Symbol, Address>(&Symbol::new(e, GUARDIAN_KEY))
+}
+
+/// Set if every asset is paused
+pub fn set_paused(e: &Env, paused: bool) {
+    e.storage()
+        .instance()
+        .set::<Symbol, bool>(&Symbol::new(e, PAUSED_KEY), &paused);
+}
+
+/// Get if every asset is paused
+pub fn get_paused(e: &Env) -> bool {
+    e.storage()
+        .instance()
+        .get::<Symbol, bool>(&Symbol::new(e, PAUSED_KEY))
+        .unwrap_or(false)
+}
+
+/// Set the assets that are paused individually
+pub fn set_paused_assets(e: &Env, assets: &Vec<Asset>) {
+    e.storage()
+        .instance()
+        .set::<Symbol, Vec<Asset>>(&Symbol::new(e, PAUSED_ASSETS_KEY), assets);
+}
+
+/// Get the assets that are paused individually
+pub fn get_paused_assets(e: &Env) -> Vec<Asset> {
+    e.storage()
+        .instance()
+        .get::<Symbol, Vec<Asset>>(&Symbol::new(e, PAUSED_ASSETS_KEY))
+        .unwrap_or(Vec::new(e))
+}
+
 /// Set the max age of a price, in seconds
 pub fn set_max_age(e: &Env, max_age: &u64) {
     e.storage()
@@ -43,6 +141,21 @@ pub fn get_max_age(e: &Env) -> u64 {
         .unwrap_optimized()
 }
 
+/// Set the grace period a cached price can be served for after it exceeds the max age, in seconds
+pub fn set_grace_period(e: &Env, grace_period: &u64) {
+    e.storage()
+        .instance()
+        .set::<Symbol, u64>(&Symbol::new(e, GRACE_PERIOD_KEY), grace_period);
+}
+
+/// Get the grace period a cached price can be served for after it exceeds the max age, in seconds
+pub fn get_grace_period(e: &Env) -> u64 {
+    e.storage()
+        .instance()
+        .get::<Symbol, u64>(&Symbol::new(e, GRACE_PERIOD_KEY))
+        .unwrap_or(0)
+}
+
 /// Set the number of decimals the oracle will report prices in
 pub fn set_decimals(e: &Env, decimals: &u32) {
     e.storage()
@@ -58,6 +171,37 @@ pub fn get_decimals(e: &Env) -> u32 {
         .unwrap()
 }
 
+/// Set the rounding applied when a price loses precision
+pub fn set_rounding(e: &Env, rounding: &RoundingMode) {
+    e.storage()
+        .instance()
+        .set::<Symbol, RoundingMode>(&Symbol::new(e, ROUNDING_KEY), rounding);
+}
+
+/// Get the rounding applied when a price loses precision. Defaults to rounding down.
+pub fn get_rounding(e: &Env) -> RoundingMode {
+    e.storage()
+        .instance()
+        .get::<Symbol, RoundingMode>(&Symbol::new(e, ROUNDING_KEY))
+        .unwrap_or(RoundingMode::Floor)
+}
+
+/// Set the strategy used to query a source oracle for an older round
+pub fn set_retry_strategy(e: &Env, retry_strategy: &RetryStrategy) {
+    e.storage()
+        .instance()
+        .set::<Symbol, RetryStrategy>(&Symbol::new(e, RETRY_STRATEGY_KEY), retry_strategy);
+}
+
+/// Get the strategy used to query a source oracle for an older round. Defaults to calling
+/// `price` once per `resolution` step.
+pub fn get_retry_strategy(e: &Env) -> RetryStrategy {
+    e.storage()
+        .instance()
+        .get::<Symbol, RetryStrategy>(&Symbol::new(e, RETRY_STRATEGY_KEY))
+        .unwrap_or(RetryStrategy::Price)
+}
+
 /// Set the USDC asset address
 pub fn set_usdc(e: &Env, usdc: &Address) {
     e.storage()
@@ -73,17 +217,365 @@ pub fn get_usdc(e: &Env) -> Address {
         .unwrap()
 }
 
-/// Set the oracle config
-pub fn set_oracle_config(e: &Env, config: &OracleConfig) {
+/// Set the source of the price of USDC in USD, or remove it if None
+pub fn set_usdc_source(e: &Env, source: &Option<UsdcSource>) {
+    let key = Symbol::new(e, USDC_SOURCE_KEY);
+    match source {
+        Some(source) => e
+            .storage()
+            .instance()
+            .set::<Symbol, UsdcSource>(&key, source),
+        None => e.storage().instance().remove::<Symbol>(&key),
+    }
+}
+
+/// Get the source of the price of USDC in USD, if it exists
+pub fn get_usdc_source(e: &Env) -> Option<UsdcSource> {
+    e.storage()
+        .instance()
+        .get::<Symbol, UsdcSource>(&Symbol::new(e, USDC_SOURCE_KEY))
+}
+
+/// Set the conversion of prices reported in USD by source oracles to USDC, or remove it if None
+pub fn set_usd_conversion(e: &Env, conversion: &Option<UsdConversionConfig>) {
+    let key = Symbol::new(e, USD_CONVERSION_KEY);
+    match conversion {
+        Some(conversion) => e
+            .storage()
+            .instance()
+            .set::<Symbol, UsdConversionConfig>(&key, conversion),
+        None => e.storage().instance().remove::<Symbol>(&key),
+    }
+}
+
+/// Get the conversion of prices reported in USD by source oracles to USDC, if it exists
+pub fn get_usd_conversion(e: &Env) -> Option<UsdConversionConfig> {
+    e.storage()
+        .instance()
+        .get::<Symbol, UsdConversionConfig>(&Symbol::new(e, USD_CONVERSION_KEY))
+}
+
+/// Set the source oracle configs
+pub fn set_oracle_configs(e: &Env, configs: &Vec<OracleConfig>) {
     e.storage()
         .instance()
-        .set::<Symbol, OracleConfig>(&Symbol::new(e, ORACLE_KEY), config);
+        .set::<Symbol, Vec<OracleConfig>>(&Symbol::new(e, ORACLES_KEY), configs);
 }
 
-/// Get the oracle config
-pub fn get_oracle_config(e: &Env) -> OracleConfig {
+/// Get the source oracle configs
+pub fn get_oracle_configs(e: &Env) -> Vec<OracleConfig> {
     e.storage()
         .instance()
-        .get::<Symbol, OracleConfig>(&Symbol::new(e, ORACLE_KEY))
+        .get::<Symbol, Vec<OracleConfig>>(&Symbol::new(e, ORACLES_KEY))
         .unwrap()
 }
+
+/// Set the assets with an asset config
+pub fn set_assets(e: &Env, assets: &Vec<Asset>) {
+    e.storage()
+        .instance()
+        .set::<Symbol, Vec<Asset>>(&Symbol::new(e, ASSETS_KEY), assets);
+}
+
+/// Get the assets with an asset config
+pub fn get_assets(e: &Env) -> Vec<Asset> {
+    e.storage()
+        .instance()
+        .get::<Symbol, Vec<Asset>>(&Symbol::new(e, ASSETS_KEY))
+        .unwrap_or(Vec::new(e))
+}
+
+/// Set the assets priced from an underlying asset
+pub fn set_derived_assets(e: &Env, assets: &Vec<Asset>) {
+    e.storage()
+        .instance()
+        .set::<Symbol, Vec<Asset>>(&Symbol::new(e, DERIVED_ASSETS_KEY), assets);
+}
+
+/// Get the assets priced from an underlying asset
+pub fn get_derived_assets(e: &Env) -> Vec<Asset> {
+    e.storage()
+        .instance()
+        .get::<Symbol, Vec<Asset>>(&Symbol::new(e, DERIVED_ASSETS_KEY))
+        .unwrap_or(Vec::new(e))
+}
+
+/// Set the registry of pegged assets
+pub fn set_pegged_assets(e: &Env, pegged_assets: &Map<Asset, PeggedAsset>) {
+    e.storage()
+        .instance()
+        .set::<Symbol, Map<Asset, PeggedAsset>>(&Symbol::new(e, PEGGED_ASSETS_KEY), pegged_assets);
+}
+
+/// Get the registry of pegged assets
+pub fn get_pegged_assets(e: &Env) -> Map<Asset, PeggedAsset> {
+    e.storage()
+        .instance()
+        .get::<Symbol, Map<Asset, PeggedAsset>>(&Symbol::new(e, PEGGED_ASSETS_KEY))
+        .unwrap_or(Map::new(e))
+}
+
+/// Set the assets priced through a path of oracles
+pub fn set_path_assets(e: &Env, assets: &Vec<Asset>) {
+    e.storage()
+        .instance()
+        .set::<Symbol, Vec<Asset>>(&Symbol::new(e, PATH_ASSETS_KEY), assets);
+}
+
+/// Get the assets priced through a path of oracles
+pub fn get_path_assets(e: &Env) -> Vec<Asset> {
+    e.storage()
+        .instance()
+        .get::<Symbol, Vec<Asset>>(&Symbol::new(e, PATH_ASSETS_KEY))
+        .unwrap_or(Vec::new(e))
+}
+
+/// Set the max deviation between consecutive rounds for an asset, or remove it if None
+///
+/// Max deviations are kept in instance storage, so assets without one are priced without
+/// reading an extra entry.
+pub fn set_max_deviation(e: &Env, asset: &Asset, max_deviation: &Option<u32>) {
+    let mut max_deviations = get_max_deviations(e);
+    match max_deviation {
+        Some(max_deviation) => max_deviations.set(asset.clone(), *max_deviation),
+        None => {
+            max_deviations.remove(asset.clone());
+        }
+    }
+    e.storage()
+        .instance()
+        .set::<Symbol, Map<Asset, u32>>(&Symbol::new(e, MAX_DEVIATIONS_KEY), &max_deviations);
+}
+
+/// Get the max deviation between consecutive rounds for an asset, if it exists
+pub fn get_max_deviation(e: &Env, asset: &Asset) -> Option<u32> {
+    get_max_deviations(e).get(asset.clone())
+}
+
+/// Get the max deviation between consecutive rounds of each asset that has one
+fn get_max_deviations(e: &Env) -> Map<Asset, u32> {
+    e.storage()
+        .instance()
+        .get::<Symbol, Map<Asset, u32>>(&Symbol::new(e, MAX_DEVIATIONS_KEY))
+        .unwrap_or(Map::new(e))
+}
+
+/********** Persistent **********/
+
+/// Set the config for an asset
+pub fn set_asset_config(e: &Env, asset: &Asset, config: &AssetConfig) {
+    let key = AggregatorDataKey::Asset(asset.clone());
+    e.storage()
+        .persistent()
+        .set::<AggregatorDataKey, AssetConfig>(&key, config);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, LEDGER_THRESHOLD, LEDGER_BUMP);
+}
+
+/// Get the config for an asset, if it exists
+pub fn get_asset_config(e: &Env, asset: &Asset) -> Option<AssetConfig> {
+    let key = AggregatorDataKey::Asset(asset.clone());
+    let config = e
+        .storage()
+        .persistent()
+        .get::<AggregatorDataKey, AssetConfig>(&key);
+    if config.is_some() {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, LEDGER_THRESHOLD, LEDGER_BUMP);
+    }
+    config
+}
+
+/// Set the price bounds of an asset, or remove them if None
+pub fn set_price_bounds(e: &Env, asset: &Asset, bounds: &Option<PriceBounds>) {
+    let key = AggregatorDataKey::PriceBounds(asset.clone());
+    match bounds {
+        Some(bounds) => {
+            e.storage()
+                .persistent()
+                .set::<AggregatorDataKey, PriceBounds>(&key, bounds);
+            e.storage()
+                .persistent()
+                .extend_ttl(&key, LEDGER_THRESHOLD, LEDGER_BUMP);
+        }
+        None => e.storage().persistent().remove(&key),
+    }
+}
+
+/// Get the price bounds of an asset, if they exist
+pub fn get_price_bounds(e: &Env, asset: &Asset) -> Option<PriceBounds> {
+    let key = AggregatorDataKey::PriceBounds(asset.clone());
+    let bounds = e
+        .storage()
+        .persistent()
+        .get::<AggregatorDataKey, PriceBounds>(&key);
+    if bounds.is_some() {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, LEDGER_THRESHOLD, LEDGER_BUMP);
+    }
+    bounds
+}
+
+/// Set the underlying asset an asset is priced from, or remove it if None
+pub fn set_derived_asset(e: &Env, asset: &Asset, derived: &Option<DerivedAsset>) {
+    let key = AggregatorDataKey::Derived(asset.clone());
+    match derived {
+        Some(derived) => {
+            e.storage()
+                .persistent()
+                .set::<AggregatorDataKey, DerivedAsset>(&key, derived);
+            e.storage()
+                .persistent()
+                .extend_ttl(&key, LEDGER_THRESHOLD, LEDGER_BUMP);
+        }
+        None => e.storage().persistent().remove(&key),
+    }
+}
+
+/// Get the underlying asset an asset is priced from, if it exists
+pub fn get_derived_asset(e: &Env, asset: &Asset) -> Option<DerivedAsset> {
+    // the list of derived assets is in instance storage, so most assets skip the persistent read
+    if !get_derived_assets(e).contains(asset) {
+        return None;
+    }
+    let key = AggregatorDataKey::Derived(asset.clone());
+    let derived = e
+        .storage()
+        .persistent()
+        .get::<AggregatorDataKey, DerivedAsset>(&key);
+    if derived.is_some() {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, LEDGER_THRESHOLD, LEDGER_BUMP);
+    }
+    derived
+}
+
+/// Set the path of oracles an asset is priced through, or remove it if None
+pub fn set_asset_path(e: &Env, asset: &Asset, path: &Option<Vec<PathLeg>>) {
+    let key = AggregatorDataKey::Path(asset.clone());
+    match path {
+        Some(path) => {
+            e.storage()
+                .persistent()
+                .set::<AggregatorDataKey, Vec<PathLeg>>(&key, path);
+            e.storage()
+                .persistent()
+                .extend_ttl(&key, LEDGER_THRESHOLD, LEDGER_BUMP);
+        }
+        None => e.storage().persistent().remove(&key),
+    }
+}
+
+/// Get the path of oracles an asset is priced through, if it exists
+pub fn get_asset_path(e: &Env, asset: &Asset) -> Option<Vec<PathLeg>> {
+    // the list of path assets is in instance storage, so most assets skip the persistent read
+    if !get_path_assets(e).contains(asset) {
+        return None;
+    }
+    let key = AggregatorDataKey::Path(asset.clone());
+    let path = e
+        .storage()
+        .persistent()
+        .get::<AggregatorDataKey, Vec<PathLeg>>(&key);
+    if path.is_some() {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, LEDGER_THRESHOLD, LEDGER_BUMP);
+    }
+    path
+}
+
+/// Set the last known good price of an asset
+pub fn set_last_price(e: &Env, asset: &Asset, price: &PriceData) {
+    let key = AggregatorDataKey::LastPrice(asset.clone());
+    e.storage()
+        .persistent()
+        .set::<AggregatorDataKey, PriceData>(&key, price);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, LEDGER_THRESHOLD, LEDGER_BUMP);
+}
+
+/// Get the last known good price of an asset, if it exists
+pub fn get_last_price(e: &Env, asset: &Asset) -> Option<PriceData> {
+    let key = AggregatorDataKey::LastPrice(asset.clone());
+    let price = e
+        .storage()
+        .persistent()
+        .get::<AggregatorDataKey, PriceData>(&key);
+    if price.is_some() {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, LEDGER_THRESHOLD, LEDGER_BUMP);
+    }
+    price
+}
+
+/// Remove the last known good price of an asset
+pub fn remove_last_price(e: &Env, asset: &Asset) {
+    e.storage()
+        .persistent()
+        .remove(&AggregatorDataKey::LastPrice(asset.clone()));
+}
+
+/// Set the symbol a Stellar asset is aliased to, and the reverse mapping
+pub fn set_alias(e: &Env, address: &Address, symbol: &Symbol) {
+    let symbol_key = AggregatorDataKey::AliasSymbol(address.clone());
+    let address_key = AggregatorDataKey::AliasAddress(symbol.clone());
+    e.storage()
+        .persistent()
+        .set::<AggregatorDataKey, Symbol>(&symbol_key, symbol);
+    e.storage()
+        .persistent()
+        .set::<AggregatorDataKey, Address>(&address_key, address);
+    e.storage()
+        .persistent()
+        .extend_ttl(&symbol_key, LEDGER_THRESHOLD, LEDGER_BUMP);
+    e.storage()
+        .persistent()
+        .extend_ttl(&address_key, LEDGER_THRESHOLD, LEDGER_BUMP);
+}
+
+/// Remove the alias of a Stellar asset, and the reverse mapping
+pub fn remove_alias(e: &Env, address: &Address, symbol: &Symbol) {
+    e.storage()
+        .persistent()
+        .remove(&AggregatorDataKey::AliasSymbol(address.clone()));
+    e.storage()
+        .persistent()
+        .remove(&AggregatorDataKey::AliasAddress(symbol.clone()));
+}
+
+/// Get the symbol a Stellar asset is aliased to, if it exists
+pub fn get_alias_symbol(e: &Env, address: &Address) -> Option<Symbol> {
+    let key = AggregatorDataKey::AliasSymbol(address.clone());
+    let symbol = e
+        .storage()
+        .persistent()
+        .get::<AggregatorDataKey, Symbol>(&key);
+    if symbol.is_some() {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, LEDGER_THRESHOLD, LEDGER_BUMP);
+    }
+    symbol
+}
+
+/// Get the Stellar asset a symbol is an alias of, if it exists
+pub fn get_alias_address(e: &Env, symbol: &Symbol) -> Option<Address> {
+    let key = AggregatorDataKey::AliasAddress(symbol.clone());
+    let address = e
+        .storage()
+        .persistent()
+        .get::<AggregatorDataKey, Address>(&key);
+    if address.is_some() {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, LEDGER_THRESHOLD, LEDGER_BUMP);
+    }
+    address
+}
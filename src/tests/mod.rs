@@ -1,2 +1,27 @@
+// prices are written as `<whole>_<decimals>` (e.g. `0_1200000`) for readability
+#![allow(clippy::zero_prefixed_literal)]
+
+mod test_admin;
+mod test_alias;
+mod test_derived;
+mod test_deviation;
+mod test_events;
+mod test_fallback;
+mod test_grace;
 mod test_init;
 mod test_lastprice;
+mod test_lastprice_detail;
+mod test_lastprices;
+mod test_path;
+mod test_pause;
+mod test_pegged;
+mod test_price;
+mod test_price_bounds;
+mod test_prices;
+mod test_retry_strategy;
+mod test_rounding;
+mod test_twap;
+mod test_upgrade;
+mod test_usd_conversion;
+mod test_usdc;
+mod test_x_lastprice;
@@ -0,0 +1,403 @@
+#![cfg(test)]
+use crate::{
+    testutils::{create_asset_configs, create_mock_oracle, create_oracle_aggregator, EnvTestUtils},
+    types::{AssetConfig, AssetSource},
+};
+use sep_40_oracle::Asset;
+use soroban_sdk::{
+    testutils::{Address as _, AuthorizedFunction, AuthorizedInvocation},
+    vec, Address, Env, IntoVal, Symbol, Vec,
+};
+
+#[test]
+fn test_set_max_age() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let oracle = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let oracles = vec![&e, oracle.address.clone()];
+    let assets = create_asset_configs(&e, &oracles, &vec![&e, asset_0.clone()]);
+    let (aggregator_id, oracle_aggregator_client) =
+        create_oracle_aggregator(&e, &admin, &oracles, &usdc, &7, &900, &assets, &None);
+
+    oracle_aggregator_client.set_max_age(&1800);
+    assert_eq!(
+        e.auths()[0],
+        (
+            admin.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    aggregator_id.clone(),
+                    Symbol::new(&e, "set_max_age"),
+                    vec![&e, 1800u64.into_val(&e)]
+                )),
+                sub_invocations: std::vec![]
+            }
+        )
+    );
+    assert_eq!(oracle_aggregator_client.max_age(), 1800);
+
+    // a price older than the previous max age is now valid
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    oracle.set_price(
+        &Vec::from_array(&e, [0_120000000, 1_010000000]),
+        &(recent_norm_time - 1200),
+    );
+    let price_0 = oracle_aggregator_client
+        .lastprice(&Asset::Stellar(asset_0))
+        .unwrap();
+    assert_eq!(price_0.price, 0_1200000);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #102)")]
+fn test_set_max_age_invalid() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let oracle = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let (_, oracle_aggregator_client) = create_oracle_aggregator(
+        &e,
+        &Address::generate(&e),
+        &vec![&e, oracle.address.clone()],
+        &usdc,
+        &7,
+        &900,
+        &vec![&e],
+        &None,
+    );
+
+    oracle_aggregator_client.set_max_age(&3601);
+}
+
+#[test]
+#[should_panic(expected = "Error(Auth, InvalidAction)")]
+fn test_set_max_age_requires_admin() {
+    let e = Env::default();
+    e.set_default_info();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    e.mock_all_auths();
+    let oracle = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let (_, oracle_aggregator_client) = create_oracle_aggregator(
+        &e,
+        &Address::generate(&e),
+        &vec![&e, oracle.address.clone()],
+        &usdc,
+        &7,
+        &900,
+        &vec![&e],
+        &None,
+    );
+
+    e.set_auths(&[]);
+    oracle_aggregator_client.set_max_age(&1800);
+}
+
+#[test]
+fn test_set_decimals() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let oracle = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let oracles = vec![&e, oracle.address.clone()];
+    let assets = create_asset_configs(&e, &oracles, &vec![&e, asset_0.clone()]);
+    let (aggregator_id, oracle_aggregator_client) =
+        create_oracle_aggregator(&e, &admin, &oracles, &usdc, &7, &900, &assets, &None);
+
+    oracle_aggregator_client.set_decimals(&12);
+    assert_eq!(
+        e.auths()[0],
+        (
+            admin.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    aggregator_id.clone(),
+                    Symbol::new(&e, "set_decimals"),
+                    vec![&e, 12u32.into_val(&e)]
+                )),
+                sub_invocations: std::vec![]
+            }
+        )
+    );
+    assert_eq!(oracle_aggregator_client.decimals(), 12);
+
+    oracle.set_price(
+        &Vec::from_array(&e, [0_110000000, 1_000000000]),
+        &e.ledger().timestamp(),
+    );
+    let price_0 = oracle_aggregator_client
+        .lastprice(&Asset::Stellar(asset_0))
+        .unwrap();
+    assert_eq!(price_0.price, 0_110000000000);
+    let price_usdc = oracle_aggregator_client
+        .lastprice(&Asset::Stellar(usdc))
+        .unwrap();
+    assert_eq!(price_usdc.price, 1_000000000000);
+}
+
+#[test]
+fn test_set_oracles() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let oracle_0 = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let oracle_1 = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &14, &600);
+    let oracles = vec![&e, oracle_0.address.clone()];
+    let assets = create_asset_configs(&e, &oracles, &vec![&e, asset_0.clone()]);
+    let (aggregator_id, oracle_aggregator_client) =
+        create_oracle_aggregator(&e, &admin, &oracles, &usdc, &7, &900, &assets, &None);
+
+    let new_oracles = vec![&e, oracle_0.address.clone(), oracle_1.address.clone()];
+    oracle_aggregator_client.set_oracles(&new_oracles);
+    assert_eq!(
+        e.auths()[0],
+        (
+            admin.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    aggregator_id.clone(),
+                    Symbol::new(&e, "set_oracles"),
+                    vec![&e, new_oracles.into_val(&e)]
+                )),
+                sub_invocations: std::vec![]
+            }
+        )
+    );
+    let configs = oracle_aggregator_client.config();
+    assert_eq!(configs.len(), 2);
+    assert_eq!(configs.get_unchecked(1).oracle_id, oracle_1.address);
+    assert_eq!(configs.get_unchecked(1).decimals, 14);
+    assert_eq!(configs.get_unchecked(1).resolution, 600);
+    assert_eq!(oracle_aggregator_client.resolution(), 600);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #103)")]
+fn test_set_oracles_base_not_usdc() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let oracle_0 = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let oracle_1 = create_mock_oracle(&e, &asset_0, &usdc, &asset_1, &9, &300);
+    let (_, oracle_aggregator_client) = create_oracle_aggregator(
+        &e,
+        &Address::generate(&e),
+        &vec![&e, oracle_0.address.clone()],
+        &usdc,
+        &7,
+        &900,
+        &vec![&e],
+        &None,
+    );
+
+    oracle_aggregator_client.set_oracles(&vec![&e, oracle_1.address.clone()]);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #101)")]
+fn test_set_oracles_removes_oracle_used_by_asset() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let oracle_0 = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let oracle_1 = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let oracles = vec![&e, oracle_0.address.clone()];
+    let assets = create_asset_configs(&e, &oracles, &vec![&e, asset_0.clone()]);
+    let (_, oracle_aggregator_client) = create_oracle_aggregator(
+        &e,
+        &Address::generate(&e),
+        &oracles,
+        &usdc,
+        &7,
+        &900,
+        &assets,
+        &None,
+    );
+
+    oracle_aggregator_client.set_oracles(&vec![&e, oracle_1.address.clone()]);
+}
+
+#[test]
+fn test_set_asset_config() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let oracle = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let oracles = vec![&e, oracle.address.clone()];
+    let assets = create_asset_configs(&e, &oracles, &vec![&e, asset_0.clone()]);
+    let (aggregator_id, oracle_aggregator_client) =
+        create_oracle_aggregator(&e, &admin, &oracles, &usdc, &7, &900, &assets, &None);
+    assert_eq!(oracle_aggregator_client.assets().len(), 2);
+
+    let asset_config = AssetConfig {
+        sources: vec![
+            &e,
+            AssetSource {
+                oracle_id: oracle.address.clone(),
+                asset: Asset::Stellar(asset_1.clone()),
+            },
+        ],
+        fallbacks: vec![&e],
+        max_age: Some(600),
+    };
+    oracle_aggregator_client.set_asset_config(&Asset::Stellar(asset_1.clone()), &asset_config);
+    assert_eq!(
+        e.auths()[0],
+        (
+            admin.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    aggregator_id.clone(),
+                    Symbol::new(&e, "set_asset_config"),
+                    vec![
+                        &e,
+                        Asset::Stellar(asset_1.clone()).into_val(&e),
+                        asset_config.into_val(&e)
+                    ]
+                )),
+                sub_invocations: std::vec![]
+            }
+        )
+    );
+    assert_eq!(oracle_aggregator_client.assets().len(), 3);
+    assert_eq!(
+        oracle_aggregator_client
+            .asset_config(&Asset::Stellar(asset_1.clone()))
+            .max_age,
+        Some(600)
+    );
+
+    oracle.set_price(
+        &Vec::from_array(&e, [0_110000000, 1_000000000]),
+        &e.ledger().timestamp(),
+    );
+    let price_1 = oracle_aggregator_client
+        .lastprice(&Asset::Stellar(asset_1))
+        .unwrap();
+    assert_eq!(price_1.price, 1_0000000);
+
+    // updating an existing asset does not duplicate it
+    let asset_0_config = create_asset_configs(&e, &oracles, &vec![&e, asset_0.clone()])
+        .get_unchecked(0)
+        .1;
+    oracle_aggregator_client.set_asset_config(&Asset::Stellar(asset_0), &asset_0_config);
+    assert_eq!(oracle_aggregator_client.assets().len(), 3);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #101)")]
+fn test_set_asset_config_invalid() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let oracle = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let (_, oracle_aggregator_client) = create_oracle_aggregator(
+        &e,
+        &Address::generate(&e),
+        &vec![&e, oracle.address.clone()],
+        &usdc,
+        &7,
+        &900,
+        &vec![&e],
+        &None,
+    );
+
+    oracle_aggregator_client.set_asset_config(
+        &Asset::Stellar(asset_0.clone()),
+        &AssetConfig {
+            sources: vec![
+                &e,
+                AssetSource {
+                    oracle_id: Address::generate(&e),
+                    asset: Asset::Stellar(asset_0),
+                },
+            ],
+            fallbacks: vec![&e],
+            max_age: None,
+        },
+    );
+}
+
+#[test]
+fn test_set_admin() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let new_admin = Address::generate(&e);
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let oracle = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let (aggregator_id, oracle_aggregator_client) = create_oracle_aggregator(
+        &e,
+        &admin,
+        &vec![&e, oracle.address.clone()],
+        &usdc,
+        &7,
+        &900,
+        &vec![&e],
+        &None,
+    );
+    assert_eq!(oracle_aggregator_client.admin(), admin);
+
+    oracle_aggregator_client.set_admin(&new_admin);
+    let auths = e.auths();
+    assert_eq!(auths.len(), 2);
+    assert_eq!(
+        auths[0],
+        (
+            admin.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    aggregator_id.clone(),
+                    Symbol::new(&e, "set_admin"),
+                    vec![&e, new_admin.into_val(&e)]
+                )),
+                sub_invocations: std::vec![]
+            }
+        )
+    );
+    assert_eq!(auths[1].0, new_admin);
+    assert_eq!(oracle_aggregator_client.admin(), new_admin);
+}
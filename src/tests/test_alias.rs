@@ -0,0 +1,235 @@
+#![cfg(test)]
+use crate::{
+    testutils::{assert_assets_equal, setup_default_aggregator, EnvTestUtils},
+    types::{AssetConfig, AssetSource},
+};
+use sep_40_oracle::{
+    testutils::{Asset as MockAsset, MockPriceOracleClient, MockPriceOracleWASM},
+    Asset,
+};
+use soroban_sdk::{
+    testutils::{Address as _, AuthorizedFunction, AuthorizedInvocation},
+    vec, Address, Env, IntoVal, Symbol, Vec,
+};
+
+#[test]
+fn test_set_alias() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+    let token = Symbol::new(&e, "TOKEN");
+
+    let symbol = Some(token.clone());
+    oracle_aggregator_client.set_alias(&asset_0, &symbol);
+    assert_eq!(
+        e.auths()[0],
+        (
+            oracle_aggregator_client.admin(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    oracle_aggregator_client.address.clone(),
+                    Symbol::new(&e, "set_alias"),
+                    vec![&e, asset_0.to_val(), symbol.into_val(&e)]
+                )),
+                sub_invocations: std::vec![]
+            }
+        )
+    );
+    assert_eq!(
+        oracle_aggregator_client.alias(&asset_0),
+        Some(token.clone())
+    );
+    assert_eq!(
+        oracle_aggregator_client.alias_address(&token),
+        Some(asset_0.clone())
+    );
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_110000000, 1_000000000]),
+        &recent_norm_time,
+    );
+
+    // the symbol resolves to the config of the Stellar asset
+    let price = oracle_aggregator_client
+        .lastprice(&Asset::Other(token.clone()))
+        .unwrap();
+    assert_eq!(price.price, 0_1100000);
+    assert_eq!(price.timestamp, recent_norm_time);
+
+    // assets are reported by their Stellar address
+    let assets = oracle_aggregator_client.assets();
+    assert!(assert_assets_equal(
+        assets.get_unchecked(0),
+        Asset::Stellar(asset_0.clone())
+    ));
+
+    // aliasing the symbol to another asset replaces the previous alias
+    oracle_aggregator_client.set_alias(&asset_1, &Some(token.clone()));
+    assert!(oracle_aggregator_client.alias(&asset_0).is_none());
+    assert_eq!(
+        oracle_aggregator_client.alias(&asset_1),
+        Some(token.clone())
+    );
+    let price = oracle_aggregator_client
+        .lastprice(&Asset::Other(token.clone()))
+        .unwrap();
+    assert_eq!(price.price, 1_0000000);
+
+    // removing the alias leaves the symbol without an asset config
+    oracle_aggregator_client.set_alias(&asset_1, &None);
+    assert!(oracle_aggregator_client.alias(&asset_1).is_none());
+    assert!(oracle_aggregator_client.alias_address(&token).is_none());
+    let result = oracle_aggregator_client.try_lastprice(&Asset::Other(token));
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_alias_usdc() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+    let usdc_symbol = Symbol::new(&e, "USDC");
+    let xlm_symbol = Symbol::new(&e, "XLM");
+    oracle_aggregator_client.set_alias(&usdc, &Some(usdc_symbol.clone()));
+
+    // USDC is priced at 1 through its alias
+    let price = oracle_aggregator_client
+        .lastprice(&Asset::Other(usdc_symbol.clone()))
+        .unwrap();
+    assert_eq!(price.price, 1_0000000);
+
+    // an oracle that uses the alias as its base asset reports prices in USDC
+    let cex_oracle_id = Address::generate(&e);
+    e.register_at(&cex_oracle_id, MockPriceOracleWASM, ());
+    let cex_oracle = MockPriceOracleClient::new(&e, &cex_oracle_id);
+    cex_oracle.set_data(
+        &Address::generate(&e),
+        &MockAsset::Other(usdc_symbol),
+        &vec![&e, MockAsset::Other(xlm_symbol.clone())],
+        &14,
+        &300,
+    );
+    oracle_aggregator_client.set_oracles(&vec![
+        &e,
+        oracle_1.address.clone(),
+        cex_oracle_id.clone(),
+    ]);
+    oracle_aggregator_client.set_asset_config(
+        &Asset::Stellar(asset_1.clone()),
+        &AssetConfig {
+            sources: vec![
+                &e,
+                AssetSource {
+                    oracle_id: cex_oracle_id.clone(),
+                    asset: Asset::Other(xlm_symbol.clone()),
+                },
+            ],
+            fallbacks: Vec::new(&e),
+            max_age: None,
+        },
+    );
+    oracle_aggregator_client.set_alias(&asset_1, &Some(xlm_symbol.clone()));
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    cex_oracle.set_price(&Vec::from_array(&e, [0_12000000000000]), &recent_norm_time);
+
+    let price = oracle_aggregator_client
+        .lastprice(&Asset::Other(xlm_symbol))
+        .unwrap();
+    assert_eq!(price.price, 0_1200000);
+    assert_eq!(price.timestamp, recent_norm_time);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #112)")]
+fn test_set_alias_symbol_with_asset_config() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+    let token = Symbol::new(&e, "TOKEN");
+    oracle_aggregator_client.set_asset_config(
+        &Asset::Other(token.clone()),
+        &AssetConfig {
+            sources: vec![
+                &e,
+                AssetSource {
+                    oracle_id: oracle_1.address.clone(),
+                    asset: Asset::Stellar(asset_0.clone()),
+                },
+            ],
+            fallbacks: Vec::new(&e),
+            max_age: None,
+        },
+    );
+
+    oracle_aggregator_client.set_alias(&asset_0, &Some(token));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #112)")]
+fn test_set_alias_paused_symbol() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, _) = setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+    let token = Symbol::new(&e, "TOKEN");
+    oracle_aggregator_client.set_guardian(&Address::generate(&e));
+    oracle_aggregator_client.pause(&Some(Asset::Other(token.clone())));
+
+    oracle_aggregator_client.set_alias(&asset_0, &Some(token));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #112)")]
+fn test_set_alias_symbol_with_max_deviation() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, _) = setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+    let token = Symbol::new(&e, "TOKEN");
+    oracle_aggregator_client.set_max_deviation(&Asset::Other(token.clone()), &Some(1000));
+
+    oracle_aggregator_client.set_alias(&asset_0, &Some(token));
+}
+
+#[test]
+#[should_panic(expected = "Error(Auth, InvalidAction)")]
+fn test_set_alias_requires_admin() {
+    let e = Env::default();
+    e.set_default_info();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, _) = setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+
+    e.set_auths(&[]);
+    oracle_aggregator_client.set_alias(&asset_0, &Some(Symbol::new(&e, "TOKEN")));
+}
@@ -0,0 +1,308 @@
+#![cfg(test)]
+use crate::{
+    testutils::{assert_assets_equal, setup_default_aggregator, EnvTestUtils},
+    types::{AssetConfig, AssetSource, DerivedAsset},
+};
+use sep_40_oracle::Asset;
+use soroban_sdk::{
+    testutils::{Address as _, AuthorizedFunction, AuthorizedInvocation},
+    vec, Address, Env, IntoVal, Symbol, Vec,
+};
+
+#[test]
+fn test_derived_asset() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+    let wrapped_0 = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+
+    let derived = Some(DerivedAsset {
+        underlying: Asset::Stellar(asset_0.clone()),
+        ratio: 9800,
+    });
+    oracle_aggregator_client.set_derived_asset(&Asset::Stellar(wrapped_0.clone()), &derived);
+    assert_eq!(
+        e.auths()[0],
+        (
+            oracle_aggregator_client.admin(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    oracle_aggregator_client.address.clone(),
+                    Symbol::new(&e, "set_derived_asset"),
+                    vec![
+                        &e,
+                        Asset::Stellar(wrapped_0.clone()).into_val(&e),
+                        derived.into_val(&e)
+                    ]
+                )),
+                sub_invocations: std::vec![]
+            }
+        )
+    );
+    let derived = oracle_aggregator_client
+        .derived_asset(&Asset::Stellar(wrapped_0.clone()))
+        .unwrap();
+    assert!(assert_assets_equal(
+        derived.underlying,
+        Asset::Stellar(asset_0.clone())
+    ));
+    assert_eq!(derived.ratio, 9800);
+
+    // derived assets are listed by `assets`, and marked by `derived_assets`
+    let derived_assets = oracle_aggregator_client.derived_assets();
+    assert_eq!(derived_assets.len(), 1);
+    assert!(assert_assets_equal(
+        derived_assets.get_unchecked(0),
+        Asset::Stellar(wrapped_0.clone())
+    ));
+    let assets = oracle_aggregator_client.assets();
+    assert_eq!(assets.len(), 4);
+    assert!(assert_assets_equal(
+        assets.get_unchecked(2),
+        Asset::Stellar(wrapped_0.clone())
+    ));
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_120000000, 1_000000000]),
+        &(recent_norm_time - 300),
+    );
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_110000000, 1_000000000]),
+        &recent_norm_time,
+    );
+
+    // the price of the underlying asset is multiplied by the ratio
+    let price = oracle_aggregator_client
+        .lastprice(&Asset::Stellar(wrapped_0.clone()))
+        .unwrap();
+    assert_eq!(price.price, 0_1078000);
+    assert_eq!(price.timestamp, recent_norm_time);
+
+    let prices = oracle_aggregator_client.lastprices(&vec![
+        &e,
+        Asset::Stellar(asset_0.clone()),
+        Asset::Stellar(wrapped_0.clone()),
+    ]);
+    assert_eq!(prices.get_unchecked(0).unwrap().price, 0_1100000);
+    assert_eq!(prices.get_unchecked(1).unwrap().price, 0_1078000);
+
+    let price = oracle_aggregator_client
+        .price(
+            &Asset::Stellar(wrapped_0.clone()),
+            &(recent_norm_time - 300),
+        )
+        .unwrap();
+    assert_eq!(price.price, 0_1176000);
+
+    let prices = oracle_aggregator_client
+        .prices(&Asset::Stellar(wrapped_0.clone()), &2)
+        .unwrap();
+    assert_eq!(prices.len(), 2);
+    assert_eq!(prices.get_unchecked(0).price, 0_1078000);
+    assert_eq!(prices.get_unchecked(1).price, 0_1176000);
+
+    let x_price = oracle_aggregator_client
+        .x_lastprice(
+            &Asset::Stellar(wrapped_0.clone()),
+            &Asset::Stellar(asset_0.clone()),
+        )
+        .unwrap();
+    assert_eq!(x_price.price, 0_9800000);
+
+    // removing the derived asset stops listing it
+    oracle_aggregator_client.set_derived_asset(&Asset::Stellar(wrapped_0.clone()), &None);
+    assert!(oracle_aggregator_client
+        .derived_asset(&Asset::Stellar(wrapped_0.clone()))
+        .is_none());
+    assert_eq!(oracle_aggregator_client.derived_assets().len(), 0);
+    assert_eq!(oracle_aggregator_client.assets().len(), 3);
+    let result = oracle_aggregator_client.try_lastprice(&Asset::Stellar(wrapped_0));
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_derived_asset_underlying_paused() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let guardian = Address::generate(&e);
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+    let wrapped_0 = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+    oracle_aggregator_client.set_guardian(&guardian);
+    oracle_aggregator_client.set_derived_asset(
+        &Asset::Stellar(wrapped_0.clone()),
+        &Some(DerivedAsset {
+            underlying: Asset::Stellar(asset_0.clone()),
+            ratio: 10_000,
+        }),
+    );
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_110000000, 1_000000000]),
+        &recent_norm_time,
+    );
+    let price = oracle_aggregator_client
+        .lastprice(&Asset::Stellar(wrapped_0.clone()))
+        .unwrap();
+    assert_eq!(price.price, 0_1100000);
+
+    oracle_aggregator_client.pause(&Some(Asset::Stellar(asset_0)));
+    let wrapped_0 = Asset::Stellar(wrapped_0);
+    assert!(oracle_aggregator_client.is_paused(&wrapped_0));
+    assert!(oracle_aggregator_client.lastprice(&wrapped_0).is_none());
+    assert!(oracle_aggregator_client
+        .price(&wrapped_0, &recent_norm_time)
+        .is_none());
+    assert!(oracle_aggregator_client.prices(&wrapped_0, &1).is_none());
+    // the cached price is not served while the underlying asset is paused
+    assert!(oracle_aggregator_client
+        .lastprice_with_grace(&wrapped_0)
+        .is_none());
+    let result = oracle_aggregator_client.try_twap(&wrapped_0, &1);
+    assert!(result.is_err());
+    assert_eq!(oracle_aggregator_client.assets().len(), 2);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #113)")]
+fn test_derived_asset_with_asset_config() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, _) = setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+
+    oracle_aggregator_client.set_derived_asset(
+        &Asset::Stellar(asset_1),
+        &Some(DerivedAsset {
+            underlying: Asset::Stellar(asset_0),
+            ratio: 10_000,
+        }),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #113)")]
+fn test_derived_asset_underlying_without_asset_config() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+    let wrapped_0 = Address::generate(&e);
+    let wrapped_wrapped_0 = Address::generate(&e);
+
+    let (oracle_aggregator_client, _) = setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+    oracle_aggregator_client.set_derived_asset(
+        &Asset::Stellar(wrapped_0.clone()),
+        &Some(DerivedAsset {
+            underlying: Asset::Stellar(asset_0),
+            ratio: 10_000,
+        }),
+    );
+
+    // a derived asset can't be the underlying asset of another derived asset
+    oracle_aggregator_client.set_derived_asset(
+        &Asset::Stellar(wrapped_wrapped_0),
+        &Some(DerivedAsset {
+            underlying: Asset::Stellar(wrapped_0),
+            ratio: 10_000,
+        }),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #113)")]
+fn test_derived_asset_zero_ratio() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, _) = setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+
+    oracle_aggregator_client.set_derived_asset(
+        &Asset::Stellar(Address::generate(&e)),
+        &Some(DerivedAsset {
+            underlying: Asset::Stellar(asset_0),
+            ratio: 0,
+        }),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #113)")]
+fn test_set_asset_config_derived_asset() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+    let wrapped_0 = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+    oracle_aggregator_client.set_derived_asset(
+        &Asset::Stellar(wrapped_0.clone()),
+        &Some(DerivedAsset {
+            underlying: Asset::Stellar(asset_0.clone()),
+            ratio: 10_000,
+        }),
+    );
+
+    oracle_aggregator_client.set_asset_config(
+        &Asset::Stellar(wrapped_0),
+        &AssetConfig {
+            sources: vec![
+                &e,
+                AssetSource {
+                    oracle_id: oracle_1.address.clone(),
+                    asset: Asset::Stellar(asset_0),
+                },
+            ],
+            fallbacks: Vec::new(&e),
+            max_age: None,
+        },
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Auth, InvalidAction)")]
+fn test_set_derived_asset_requires_admin() {
+    let e = Env::default();
+    e.set_default_info();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, _) = setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+
+    e.set_auths(&[]);
+    oracle_aggregator_client.set_derived_asset(
+        &Asset::Stellar(Address::generate(&e)),
+        &Some(DerivedAsset {
+            underlying: Asset::Stellar(asset_0),
+            ratio: 10_000,
+        }),
+    );
+}
@@ -0,0 +1,193 @@
+#![cfg(test)]
+use crate::testutils::{
+    create_asset_configs, create_mock_oracle, create_oracle_aggregator, setup_default_aggregator,
+    EnvTestUtils,
+};
+use sep_40_oracle::Asset;
+use soroban_sdk::{
+    testutils::{Address as _, Events},
+    vec, Address, Env, IntoVal, Symbol, Vec,
+};
+
+#[test]
+fn test_lastprice_within_max_deviation() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+    oracle_aggregator_client.set_max_deviation(&Asset::Stellar(asset_0.clone()), &Some(1000));
+    assert_eq!(
+        oracle_aggregator_client.max_deviation(&Asset::Stellar(asset_0.clone())),
+        Some(1000)
+    );
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_100000000, 1_000000000]),
+        &(recent_norm_time - 300),
+    );
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_110000000, 1_000000000]),
+        &recent_norm_time,
+    );
+
+    // moved exactly 10%
+    let price_0 = oracle_aggregator_client
+        .lastprice(&Asset::Stellar(asset_0))
+        .unwrap();
+    assert_eq!(price_0.price, 0_1100000);
+}
+
+#[test]
+fn test_lastprice_exceeds_max_deviation() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+    oracle_aggregator_client.set_max_deviation(&Asset::Stellar(asset_0.clone()), &Some(1000));
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_100000000, 1_000000000]),
+        &(recent_norm_time - 300),
+    );
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_089000000, 2_000000000]),
+        &recent_norm_time,
+    );
+
+    let price_0 = oracle_aggregator_client.lastprice(&Asset::Stellar(asset_0.clone()));
+    assert!(price_0.is_none());
+    // the rejected price is followed by the `price_none` event
+    let events = e.events().all();
+    let event = events.get_unchecked(events.len() - 2);
+    assert_eq!(
+        vec![&e, event],
+        vec![
+            &e,
+            (
+                oracle_aggregator_client.address.clone(),
+                (
+                    Symbol::new(&e, "price_deviation"),
+                    Asset::Stellar(asset_0.clone())
+                )
+                    .into_val(&e),
+                (oracle_1.address.clone(), 0_100000000i128, 0_089000000i128).into_val(&e)
+            )
+        ]
+    );
+
+    // asset 1 does not have a max deviation
+    let price_1 = oracle_aggregator_client
+        .lastprice(&Asset::Stellar(asset_1))
+        .unwrap();
+    assert_eq!(price_1.price, 2_0000000);
+
+    // removing the max deviation accepts the price
+    oracle_aggregator_client.set_max_deviation(&Asset::Stellar(asset_0.clone()), &None);
+    assert!(oracle_aggregator_client
+        .max_deviation(&Asset::Stellar(asset_0.clone()))
+        .is_none());
+    let price_0 = oracle_aggregator_client
+        .lastprice(&Asset::Stellar(asset_0))
+        .unwrap();
+    assert_eq!(price_0.price, 0_0890000);
+}
+
+#[test]
+fn test_lastprice_max_deviation_no_previous_round() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+    oracle_aggregator_client.set_max_deviation(&Asset::Stellar(asset_0.clone()), &Some(100));
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    oracle_1.set_price(&vec![&e], &(recent_norm_time - 300));
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_110000000, 1_000000000]),
+        &recent_norm_time,
+    );
+
+    let price_0 = oracle_aggregator_client
+        .lastprice(&Asset::Stellar(asset_0))
+        .unwrap();
+    assert_eq!(price_0.price, 0_1100000);
+}
+
+#[test]
+fn test_lastprice_max_deviation_drops_outlier_source() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let oracle_0 = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let oracle_1 = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let oracles = vec![&e, oracle_0.address.clone(), oracle_1.address.clone()];
+    let assets = create_asset_configs(&e, &oracles, &vec![&e, asset_0.clone()]);
+    let (_, oracle_aggregator_client) = create_oracle_aggregator(
+        &e,
+        &Address::generate(&e),
+        &oracles,
+        &usdc,
+        &7,
+        &900,
+        &assets,
+        &None,
+    );
+    oracle_aggregator_client.set_max_deviation(&Asset::Stellar(asset_0.clone()), &Some(500));
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    for oracle in [&oracle_0, &oracle_1] {
+        oracle.set_price(
+            &Vec::from_array(&e, [0_100000000, 1_000000000]),
+            &(recent_norm_time - 300),
+        );
+    }
+    oracle_0.set_price(
+        &Vec::from_array(&e, [0_102000000, 1_000000000]),
+        &recent_norm_time,
+    );
+    oracle_1.set_price(
+        &Vec::from_array(&e, [5_000000000, 1_000000000]),
+        &recent_norm_time,
+    );
+
+    let sourced_price = oracle_aggregator_client
+        .lastprice_with_source(&Asset::Stellar(asset_0))
+        .unwrap();
+    assert_eq!(sourced_price.price.price, 0_1020000);
+    assert_eq!(sourced_price.sources, vec![&e, oracle_0.address.clone()]);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #106)")]
+fn test_set_max_deviation_zero() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, _) = setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+    oracle_aggregator_client.set_max_deviation(&Asset::Stellar(asset_0), &Some(0));
+}
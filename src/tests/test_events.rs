@@ -0,0 +1,164 @@
+#![cfg(test)]
+use crate::{
+    testutils::{setup_default_aggregator, EnvTestUtils},
+    types::UsdConversion,
+};
+use sep_40_oracle::Asset;
+use soroban_sdk::{
+    testutils::{Address as _, Events},
+    vec, Address, Env, IntoVal, Symbol, Vec,
+};
+
+#[test]
+fn test_init_event() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+
+    let events = e.events().all();
+    assert_eq!(
+        vec![&e, events.last().unwrap()],
+        vec![
+            &e,
+            (
+                oracle_aggregator_client.address.clone(),
+                (Symbol::new(&e, "init"), oracle_aggregator_client.admin()).into_val(&e),
+                (
+                    vec![&e, oracle.address.clone()],
+                    usdc.clone(),
+                    7u32,
+                    900u64,
+                    Option::<UsdConversion>::None
+                )
+                    .into_val(&e)
+            )
+        ]
+    );
+}
+
+#[test]
+fn test_config_events() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, _) = setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+
+    oracle_aggregator_client.set_max_age(&600);
+    assert_eq!(
+        e.events().all(),
+        vec![
+            &e,
+            (
+                oracle_aggregator_client.address.clone(),
+                (Symbol::new(&e, "set_max_age"),).into_val(&e),
+                600u64.into_val(&e)
+            )
+        ]
+    );
+
+    oracle_aggregator_client.set_max_deviation(&Asset::Stellar(asset_0.clone()), &Some(500));
+    assert_eq!(
+        e.events().all(),
+        vec![
+            &e,
+            (
+                oracle_aggregator_client.address.clone(),
+                (
+                    Symbol::new(&e, "set_max_deviation"),
+                    Asset::Stellar(asset_0)
+                )
+                    .into_val(&e),
+                Some(500u32).into_val(&e)
+            )
+        ]
+    );
+}
+
+#[test]
+fn test_price_retry_event() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_120000000, 1_010000000]),
+        &(recent_norm_time - 600),
+    );
+    oracle_1.set_price(&Vec::from_array(&e, []), &(recent_norm_time - 300));
+    oracle_1.set_price(&Vec::from_array(&e, []), &recent_norm_time);
+
+    let price_0 = oracle_aggregator_client
+        .lastprice(&Asset::Stellar(asset_0.clone()))
+        .unwrap();
+    assert_eq!(price_0.price, 0_1200000);
+    assert_eq!(
+        e.events().all(),
+        vec![
+            &e,
+            (
+                oracle_aggregator_client.address.clone(),
+                (Symbol::new(&e, "price_retry"), Asset::Stellar(asset_0)).into_val(&e),
+                (oracle_1.address.clone(), 2u32, recent_norm_time - 600).into_val(&e)
+            )
+        ]
+    );
+}
+
+#[test]
+fn test_price_stale_events() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_120000000, 1_010000000]),
+        &(recent_norm_time - 1200),
+    );
+
+    let price_0 = oracle_aggregator_client.lastprice(&Asset::Stellar(asset_0.clone()));
+    assert!(price_0.is_none());
+    assert_eq!(
+        e.events().all(),
+        vec![
+            &e,
+            (
+                oracle_aggregator_client.address.clone(),
+                (
+                    Symbol::new(&e, "price_stale"),
+                    Asset::Stellar(asset_0.clone())
+                )
+                    .into_val(&e),
+                (oracle_1.address.clone(), Some(recent_norm_time - 1200)).into_val(&e)
+            ),
+            (
+                oracle_aggregator_client.address.clone(),
+                (Symbol::new(&e, "price_none"), Asset::Stellar(asset_0)).into_val(&e),
+                ().into_val(&e)
+            )
+        ]
+    );
+}
@@ -0,0 +1,286 @@
+#![cfg(test)]
+use crate::{
+    testutils::{create_mock_oracle, create_oracle_aggregator, EnvTestUtils},
+    types::{AssetConfig, AssetSource},
+};
+use sep_40_oracle::{testutils::MockPriceOracleClient, Asset};
+use soroban_sdk::{
+    testutils::{Address as _, Events},
+    vec, Address, Env, IntoVal, Symbol, Vec,
+};
+
+/// Build an asset config for `asset` with a primary oracle and an ordered list of fallbacks
+fn fallback_asset_config(
+    e: &Env,
+    asset: &Address,
+    primary: &MockPriceOracleClient,
+    fallbacks: &[&MockPriceOracleClient],
+) -> (Asset, AssetConfig) {
+    let mut fallback_sources = Vec::new(e);
+    for fallback in fallbacks {
+        fallback_sources.push_back(AssetSource {
+            oracle_id: fallback.address.clone(),
+            asset: Asset::Stellar(asset.clone()),
+        });
+    }
+    (
+        Asset::Stellar(asset.clone()),
+        AssetConfig {
+            sources: vec![
+                e,
+                AssetSource {
+                    oracle_id: primary.address.clone(),
+                    asset: Asset::Stellar(asset.clone()),
+                },
+            ],
+            fallbacks: fallback_sources,
+            max_age: None,
+        },
+    )
+}
+
+#[test]
+fn test_lastprice_uses_primary_source() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let primary = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let fallback = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let (_, oracle_aggregator_client) = create_oracle_aggregator(
+        &e,
+        &Address::generate(&e),
+        &vec![&e, primary.address.clone(), fallback.address.clone()],
+        &usdc,
+        &7,
+        &900,
+        &vec![
+            &e,
+            fallback_asset_config(&e, &asset_0, &primary, &[&fallback]),
+        ],
+        &None,
+    );
+
+    primary.set_price(
+        &Vec::from_array(&e, [0_110000000, 1_000000000]),
+        &e.ledger().timestamp(),
+    );
+    fallback.set_price(
+        &Vec::from_array(&e, [0_120000000, 1_010000000]),
+        &e.ledger().timestamp(),
+    );
+
+    let sourced_price = oracle_aggregator_client
+        .lastprice_with_source(&Asset::Stellar(asset_0))
+        .unwrap();
+    assert_eq!(sourced_price.price.price, 0_1100000);
+    assert_eq!(sourced_price.price.timestamp, e.ledger().timestamp());
+    assert_eq!(sourced_price.sources, vec![&e, primary.address.clone()]);
+    assert_eq!(sourced_price.fallback, None);
+}
+
+#[test]
+fn test_lastprice_falls_back_in_order() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let primary = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let fallback_0 = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let fallback_1 = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &14, &300);
+    let fallback_2 = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let (_, oracle_aggregator_client) = create_oracle_aggregator(
+        &e,
+        &Address::generate(&e),
+        &vec![
+            &e,
+            primary.address.clone(),
+            fallback_0.address.clone(),
+            fallback_1.address.clone(),
+            fallback_2.address.clone(),
+        ],
+        &usdc,
+        &7,
+        &900,
+        &vec![
+            &e,
+            fallback_asset_config(
+                &e,
+                &asset_0,
+                &primary,
+                &[&fallback_0, &fallback_1, &fallback_2],
+            ),
+        ],
+        &None,
+    );
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    // primary and first fallback are stale
+    primary.set_price(
+        &Vec::from_array(&e, [0_110000000, 1_000000000]),
+        &(recent_norm_time - 1200),
+    );
+    fallback_0.set_price(
+        &Vec::from_array(&e, [0_110000000, 1_000000000]),
+        &(recent_norm_time - 1200),
+    );
+    primary.set_price(&vec![&e], &recent_norm_time);
+    fallback_0.set_price(&vec![&e], &recent_norm_time);
+    // second fallback only has a recent price through the retry logic
+    fallback_1.set_price(
+        &Vec::from_array(&e, [0_12000000000000, 1_01000000000000]),
+        &(recent_norm_time - 300),
+    );
+    fallback_1.set_price(&vec![&e], &recent_norm_time);
+    fallback_2.set_price(
+        &Vec::from_array(&e, [0_130000000, 1_020000000]),
+        &recent_norm_time,
+    );
+
+    let sourced_price = oracle_aggregator_client
+        .lastprice_with_source(&Asset::Stellar(asset_0.clone()))
+        .unwrap();
+    assert_eq!(sourced_price.price.price, 0_1200000);
+    assert_eq!(sourced_price.price.timestamp, recent_norm_time - 300);
+    assert_eq!(sourced_price.sources, vec![&e, fallback_1.address.clone()]);
+    assert_eq!(sourced_price.fallback, Some(1));
+
+    let price = oracle_aggregator_client
+        .lastprice(&Asset::Stellar(asset_0))
+        .unwrap();
+    assert_eq!(price.price, 0_1200000);
+    assert_eq!(price.timestamp, recent_norm_time - 300);
+}
+
+#[test]
+fn test_lastprice_fallback_event() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let primary = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let fallback = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let (_, oracle_aggregator_client) = create_oracle_aggregator(
+        &e,
+        &Address::generate(&e),
+        &vec![&e, primary.address.clone(), fallback.address.clone()],
+        &usdc,
+        &7,
+        &900,
+        &vec![
+            &e,
+            fallback_asset_config(&e, &asset_0, &primary, &[&fallback]),
+        ],
+        &None,
+    );
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    primary.set_price(
+        &Vec::from_array(&e, [0_110000000, 1_000000000]),
+        &(recent_norm_time - 1200),
+    );
+    fallback.set_price(
+        &Vec::from_array(&e, [0_120000000, 1_010000000]),
+        &recent_norm_time,
+    );
+
+    let price = oracle_aggregator_client
+        .lastprice(&Asset::Stellar(asset_0.clone()))
+        .unwrap();
+    assert_eq!(price.price, 0_1200000);
+    // the stale primary source is reported before the fallback oracle answers
+    let events = e.events().all();
+    assert_eq!(events.len(), 2);
+    assert_eq!(
+        vec![&e, events.get_unchecked(1)],
+        vec![
+            &e,
+            (
+                oracle_aggregator_client.address.clone(),
+                (Symbol::new(&e, "price_fallback"), Asset::Stellar(asset_0)).into_val(&e),
+                (fallback.address.clone(), 0u32).into_val(&e)
+            )
+        ]
+    );
+}
+
+#[test]
+fn test_lastprice_all_fallbacks_stale() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let primary = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let fallback = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let (_, oracle_aggregator_client) = create_oracle_aggregator(
+        &e,
+        &Address::generate(&e),
+        &vec![&e, primary.address.clone(), fallback.address.clone()],
+        &usdc,
+        &7,
+        &900,
+        &vec![
+            &e,
+            fallback_asset_config(&e, &asset_0, &primary, &[&fallback]),
+        ],
+        &None,
+    );
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    primary.set_price(
+        &Vec::from_array(&e, [0_110000000, 1_000000000]),
+        &(recent_norm_time - 900),
+    );
+    fallback.set_price(
+        &Vec::from_array(&e, [0_120000000, 1_010000000]),
+        &(recent_norm_time - 900),
+    );
+
+    // jump 1 block to ensure the most recent prices are > 900 seconds old
+    e.jump(1);
+
+    let sourced_price =
+        oracle_aggregator_client.lastprice_with_source(&Asset::Stellar(asset_0.clone()));
+    assert!(sourced_price.is_none());
+    let price = oracle_aggregator_client.lastprice(&Asset::Stellar(asset_0));
+    assert!(price.is_none());
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #101)")]
+fn test_init_fallback_not_source_oracle() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let primary = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let fallback = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    create_oracle_aggregator(
+        &e,
+        &Address::generate(&e),
+        &vec![&e, primary.address.clone()],
+        &usdc,
+        &7,
+        &900,
+        &vec![
+            &e,
+            fallback_asset_config(&e, &asset_0, &primary, &[&fallback]),
+        ],
+        &None,
+    );
+}
@@ -0,0 +1,161 @@
+#![cfg(test)]
+use crate::testutils::{setup_default_aggregator, EnvTestUtils};
+use sep_40_oracle::Asset;
+use soroban_sdk::{
+    testutils::{Address as _, AuthorizedFunction, AuthorizedInvocation},
+    vec, Address, Env, IntoVal, Symbol, Vec,
+};
+
+#[test]
+fn test_lastprice_with_grace() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+
+    oracle_aggregator_client.set_grace_period(&1800);
+    assert_eq!(
+        e.auths()[0],
+        (
+            oracle_aggregator_client.admin(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    oracle_aggregator_client.address.clone(),
+                    Symbol::new(&e, "set_grace_period"),
+                    vec![&e, 1800u64.into_val(&e)]
+                )),
+                sub_invocations: std::vec![]
+            }
+        )
+    );
+    assert_eq!(oracle_aggregator_client.grace_period(), 1800);
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_120000000, 1_010000000]),
+        &(recent_norm_time - 300),
+    );
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_110000000, 1_000000000]),
+        &recent_norm_time,
+    );
+
+    let grace_price = oracle_aggregator_client
+        .lastprice_with_grace(&Asset::Stellar(asset_0.clone()))
+        .unwrap();
+    assert_eq!(grace_price.price.price, 0_1100000);
+    assert_eq!(grace_price.price.timestamp, recent_norm_time);
+    assert!(!grace_price.stale);
+
+    // the source oracle stops reporting prices, and the price exceeds the max age
+    e.jump(200);
+    assert!(oracle_aggregator_client
+        .lastprice(&Asset::Stellar(asset_0.clone()))
+        .is_none());
+    let grace_price = oracle_aggregator_client
+        .lastprice_with_grace(&Asset::Stellar(asset_0.clone()))
+        .unwrap();
+    assert_eq!(grace_price.price.price, 0_1100000);
+    assert_eq!(grace_price.price.timestamp, recent_norm_time);
+    assert!(grace_price.stale);
+
+    // the cached price exceeds the max age and the grace period
+    e.jump(360);
+    assert!(oracle_aggregator_client
+        .lastprice_with_grace(&Asset::Stellar(asset_0))
+        .is_none());
+
+    // USDC is not cached, but always has a price
+    let grace_price = oracle_aggregator_client
+        .lastprice_with_grace(&Asset::Stellar(usdc))
+        .unwrap();
+    assert_eq!(grace_price.price.price, 1_0000000);
+    assert!(!grace_price.stale);
+}
+
+#[test]
+fn test_lastprice_with_grace_caches_lastprices() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+    oracle_aggregator_client.set_grace_period(&600);
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_110000000, 1_000000000]),
+        &recent_norm_time,
+    );
+    oracle_aggregator_client.lastprices(&vec![
+        &e,
+        Asset::Stellar(asset_0.clone()),
+        Asset::Stellar(asset_1.clone()),
+    ]);
+
+    e.jump(200);
+    let grace_price = oracle_aggregator_client
+        .lastprice_with_grace(&Asset::Stellar(asset_1))
+        .unwrap();
+    assert_eq!(grace_price.price.price, 1_0000000);
+    assert!(grace_price.stale);
+}
+
+#[test]
+fn test_lastprice_with_grace_no_grace_period() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+    assert_eq!(oracle_aggregator_client.grace_period(), 0);
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_110000000, 1_000000000]),
+        &recent_norm_time,
+    );
+    oracle_aggregator_client.lastprice(&Asset::Stellar(asset_0.clone()));
+    // the price is not cached without a grace period
+    assert_eq!(e.cost_estimate().resources().write_entries, 0);
+
+    // the price is served up to the max age only
+    e.jump(179);
+    let grace_price = oracle_aggregator_client
+        .lastprice_with_grace(&Asset::Stellar(asset_0.clone()))
+        .unwrap();
+    assert!(!grace_price.stale);
+    e.jump(2);
+    assert!(oracle_aggregator_client
+        .lastprice_with_grace(&Asset::Stellar(asset_0))
+        .is_none());
+}
+
+#[test]
+#[should_panic(expected = "Error(Auth, InvalidAction)")]
+fn test_set_grace_period_requires_admin() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, _) = setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+
+    e.set_auths(&[]);
+    oracle_aggregator_client.set_grace_period(&1800);
+}
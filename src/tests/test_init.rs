@@ -1,10 +1,16 @@
 #![cfg(test)]
-use crate::testutils::{create_oracle_aggregator, EnvTestUtils};
+use crate::{
+    testutils::{
+        assert_assets_equal, create_asset_configs, create_mock_oracle, create_oracle_aggregator,
+        create_usd_oracle, EnvTestUtils,
+    },
+    types::{AssetConfig, AssetSource, UsdConversion, UsdConversionConfig},
+};
 use sep_40_oracle::{
     testutils::{Asset as MockAsset, MockPriceOracleClient, MockPriceOracleWASM},
     Asset,
 };
-use soroban_sdk::{testutils::Address as _, Address, Env, Vec};
+use soroban_sdk::{testutils::Address as _, vec, Address, Env, Symbol, Vec};
 
 #[test]
 fn test_init() {
@@ -28,9 +34,19 @@ fn test_init() {
         &9,
         &300,
     );
-    let (_, oracle_aggregator) =
-        create_oracle_aggregator(&e, &oracle_id, &usdc, &decimals, &max_age);
-    let config = oracle_aggregator.config();
+    let (_, oracle_aggregator) = create_oracle_aggregator(
+        &e,
+        &Address::generate(&e),
+        &vec![&e, oracle_id.clone()],
+        &usdc,
+        &decimals,
+        &max_age,
+        &vec![&e],
+        &None,
+    );
+    let configs = oracle_aggregator.config();
+    assert_eq!(configs.len(), 1);
+    let config = configs.get_unchecked(0);
     match config.base {
         Asset::Stellar(addr) => assert_eq!(addr, usdc),
         _ => panic!("Expected base asset to be USDC"),
@@ -38,6 +54,7 @@ fn test_init() {
     assert_eq!(config.decimals, 9);
     assert_eq!(config.resolution, 300);
     assert_eq!(config.oracle_id, oracle_id);
+    assert_eq!(oracle_aggregator.resolution(), 300);
     assert_eq!(max_age, oracle_aggregator.max_age());
     assert_eq!(usdc, oracle_aggregator.usdc());
 }
@@ -65,7 +82,16 @@ fn test_init_oracle_base_not_usdc() {
         &9,
         &300,
     );
-    create_oracle_aggregator(&e, &oracle_id, &usdc, &decimals, &max_age);
+    create_oracle_aggregator(
+        &e,
+        &Address::generate(&e),
+        &vec![&e, oracle_id.clone()],
+        &usdc,
+        &decimals,
+        &max_age,
+        &vec![&e],
+        &None,
+    );
 }
 
 #[test]
@@ -91,7 +117,16 @@ fn test_init_max_age_too_small() {
         &9,
         &300,
     );
-    create_oracle_aggregator(&e, &oracle_id, &usdc, &decimals, &max_age);
+    create_oracle_aggregator(
+        &e,
+        &Address::generate(&e),
+        &vec![&e, oracle_id.clone()],
+        &usdc,
+        &decimals,
+        &max_age,
+        &vec![&e],
+        &None,
+    );
 }
 
 #[test]
@@ -117,5 +152,325 @@ fn test_init_max_age_too_large() {
         &9,
         &300,
     );
-    create_oracle_aggregator(&e, &oracle_id, &usdc, &decimals, &max_age);
+    create_oracle_aggregator(
+        &e,
+        &Address::generate(&e),
+        &vec![&e, oracle_id.clone()],
+        &usdc,
+        &decimals,
+        &max_age,
+        &vec![&e],
+        &None,
+    );
+}
+
+#[test]
+fn test_init_multiple_oracles() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    e.cost_estimate().budget().reset_unlimited();
+
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let oracle_0 = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let oracle_1 = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &14, &600);
+    let oracles = vec![&e, oracle_0.address.clone(), oracle_1.address.clone()];
+    let assets = create_asset_configs(&e, &oracles, &vec![&e, asset_0.clone(), asset_1.clone()]);
+    let (_, oracle_aggregator) = create_oracle_aggregator(
+        &e,
+        &Address::generate(&e),
+        &oracles,
+        &usdc,
+        &7,
+        &900,
+        &assets,
+        &None,
+    );
+    let configs = oracle_aggregator.config();
+    assert_eq!(configs.len(), 2);
+    assert_eq!(configs.get_unchecked(0).oracle_id, oracle_0.address);
+    assert_eq!(configs.get_unchecked(0).decimals, 9);
+    assert_eq!(configs.get_unchecked(1).oracle_id, oracle_1.address);
+    assert_eq!(configs.get_unchecked(1).decimals, 14);
+    // reports the coarsest resolution
+    assert_eq!(oracle_aggregator.resolution(), 600);
+
+    let assets = oracle_aggregator.assets();
+    assert_eq!(assets.len(), 3);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #103)")]
+fn test_init_any_oracle_base_not_usdc() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    e.cost_estimate().budget().reset_unlimited();
+
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let oracle_0 = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let oracle_1 = create_mock_oracle(&e, &asset_0, &usdc, &asset_1, &9, &300);
+    create_oracle_aggregator(
+        &e,
+        &Address::generate(&e),
+        &vec![&e, oracle_0.address.clone(), oracle_1.address.clone()],
+        &usdc,
+        &7,
+        &900,
+        &vec![&e],
+        &None,
+    );
+}
+
+#[test]
+fn test_init_usd_oracle() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    e.cost_estimate().budget().reset_unlimited();
+
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+
+    // a CEX or FX oracle that reports prices in USD
+    let cex_oracle = create_usd_oracle(&e, &asset_0);
+    let oracles = vec![&e, cex_oracle.address.clone()];
+    let (_, oracle_aggregator) = create_oracle_aggregator(
+        &e,
+        &Address::generate(&e),
+        &oracles,
+        &usdc,
+        &7,
+        &900,
+        &create_asset_configs(&e, &oracles, &vec![&e, asset_0.clone()]),
+        &Some(UsdConversion::Fixed),
+    );
+    assert!(matches!(
+        oracle_aggregator.usd_conversion(),
+        Some(UsdConversionConfig::Fixed)
+    ));
+    let config = oracle_aggregator.config().get_unchecked(0);
+    assert_eq!(config.oracle_id, cex_oracle.address);
+    assert!(assert_assets_equal(
+        config.base,
+        Asset::Other(Symbol::new(&e, "USD"))
+    ));
+
+    cex_oracle.set_price(
+        &Vec::from_array(&e, [1_50000000000000]),
+        &e.ledger().timestamp(),
+    );
+    let price = oracle_aggregator
+        .lastprice(&Asset::Stellar(asset_0))
+        .unwrap();
+    assert_eq!(price.price, 1_5000000);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #103)")]
+fn test_init_usd_oracle_without_usd_conversion() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    e.cost_estimate().budget().reset_unlimited();
+
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+
+    let cex_oracle = create_usd_oracle(&e, &asset_0);
+    create_oracle_aggregator(
+        &e,
+        &Address::generate(&e),
+        &vec![&e, cex_oracle.address.clone()],
+        &usdc,
+        &7,
+        &900,
+        &vec![&e],
+        &None,
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #104)")]
+fn test_init_no_oracles() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    e.cost_estimate().budget().reset_unlimited();
+
+    let usdc = Address::generate(&e);
+    create_oracle_aggregator(
+        &e,
+        &Address::generate(&e),
+        &vec![&e],
+        &usdc,
+        &7,
+        &900,
+        &vec![&e],
+        &None,
+    );
+}
+
+#[test]
+fn test_init_asset_configs() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    e.cost_estimate().budget().reset_unlimited();
+
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let oracle_0 = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let oracle_1 = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let (_, oracle_aggregator) = create_oracle_aggregator(
+        &e,
+        &Address::generate(&e),
+        &vec![&e, oracle_0.address.clone(), oracle_1.address.clone()],
+        &usdc,
+        &7,
+        &900,
+        &vec![
+            &e,
+            (
+                Asset::Stellar(asset_0.clone()),
+                AssetConfig {
+                    sources: vec![
+                        &e,
+                        AssetSource {
+                            oracle_id: oracle_1.address.clone(),
+                            asset: Asset::Other(Symbol::new(&e, "XLM")),
+                        },
+                    ],
+                    fallbacks: vec![&e],
+                    max_age: Some(360),
+                },
+            ),
+        ],
+        &None,
+    );
+
+    let asset_config = oracle_aggregator.asset_config(&Asset::Stellar(asset_0.clone()));
+    assert_eq!(asset_config.sources.len(), 1);
+    let source = asset_config.sources.get_unchecked(0);
+    assert_eq!(source.oracle_id, oracle_1.address);
+    assert!(assert_assets_equal(
+        source.asset,
+        Asset::Other(Symbol::new(&e, "XLM"))
+    ));
+    assert_eq!(asset_config.max_age, Some(360));
+
+    let assets = oracle_aggregator.assets();
+    assert_eq!(assets.len(), 2);
+    assert!(assert_assets_equal(
+        assets.get_unchecked(0),
+        Asset::Stellar(asset_0)
+    ));
+    assert!(assert_assets_equal(
+        assets.get_unchecked(1),
+        Asset::Stellar(usdc)
+    ));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #101)")]
+fn test_init_asset_config_oracle_not_source() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    e.cost_estimate().budget().reset_unlimited();
+
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let oracle_0 = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let oracle_1 = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let assets = create_asset_configs(
+        &e,
+        &vec![&e, oracle_0.address.clone(), oracle_1.address.clone()],
+        &vec![&e, asset_0.clone()],
+    );
+    create_oracle_aggregator(
+        &e,
+        &Address::generate(&e),
+        &vec![&e, oracle_0.address.clone()],
+        &usdc,
+        &7,
+        &900,
+        &assets,
+        &None,
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #101)")]
+fn test_init_asset_config_no_sources() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    e.cost_estimate().budget().reset_unlimited();
+
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let oracle_0 = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    create_oracle_aggregator(
+        &e,
+        &Address::generate(&e),
+        &vec![&e, oracle_0.address.clone()],
+        &usdc,
+        &7,
+        &900,
+        &vec![
+            &e,
+            (
+                Asset::Stellar(asset_0.clone()),
+                AssetConfig {
+                    sources: vec![&e],
+                    fallbacks: vec![&e],
+                    max_age: None,
+                },
+            ),
+        ],
+        &None,
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #102)")]
+fn test_init_asset_config_max_age_too_large() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    e.cost_estimate().budget().reset_unlimited();
+
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let oracle_0 = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let oracles = vec![&e, oracle_0.address.clone()];
+    let mut assets = create_asset_configs(&e, &oracles, &vec![&e, asset_0.clone()]);
+    let (asset, mut asset_config) = assets.get_unchecked(0);
+    asset_config.max_age = Some(3601);
+    assets.set(0, (asset, asset_config));
+    create_oracle_aggregator(
+        &e,
+        &Address::generate(&e),
+        &oracles,
+        &usdc,
+        &7,
+        &900,
+        &assets,
+        &None,
+    );
 }
@@ -1,7 +1,13 @@
 #![cfg(test)]
 use std::println;
 
-use crate::testutils::{setup_default_aggregator, EnvTestUtils};
+use crate::{
+    testutils::{
+        create_asset_configs, create_mock_oracle, create_oracle_aggregator,
+        setup_default_aggregator, EnvTestUtils,
+    },
+    types::{AssetConfig, AssetSource},
+};
 use sep_40_oracle::Asset;
 use soroban_sdk::{testutils::Address as _, vec, Address, Env, Vec};
 
@@ -146,14 +152,15 @@ fn test_lastprice_retry_stops_if_over_max_age() {
     // jump 1 block to ensure the most recent price is > 900 seconds old
     e.jump(1);
 
-    // validate price is not found and ledger entries are less than 10
+    // validate price is not found and ledger entries are less than 11
     let price_0 = oracle_aggregator_client.lastprice(&Asset::Stellar(asset_0));
     let read_entries_0 = e.cost_estimate().resources().read_entries;
     assert!(price_0.is_none());
     // 4 read for usdc, oracle config, decimals, and max age
+    // 1 read for asset config
     // 1 read for oracle contract
     // 4 reads for price data from oracle contract
-    assert!(read_entries_0 < 10);
+    assert!(read_entries_0 < 11);
 }
 
 #[test]
@@ -179,3 +186,317 @@ fn test_lastprice_checks_if_asset_is_usdc() {
     assert_eq!(price_0.price, 1_0000000);
     assert_eq!(price_0.timestamp, e.ledger().timestamp());
 }
+
+#[test]
+fn test_lastprice_median() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let oracle_0 = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let oracle_1 = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &14, &300);
+    let oracle_2 = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &7, &300);
+    let oracles = vec![
+        &e,
+        oracle_0.address.clone(),
+        oracle_1.address.clone(),
+        oracle_2.address.clone(),
+    ];
+    let assets = create_asset_configs(&e, &oracles, &vec![&e, asset_0.clone(), asset_1.clone()]);
+    let (_, oracle_aggregator_client) = create_oracle_aggregator(
+        &e,
+        &Address::generate(&e),
+        &oracles,
+        &usdc,
+        &7,
+        &900,
+        &assets,
+        &None,
+    );
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    oracle_0.set_price(
+        &Vec::from_array(&e, [0_110000000, 1_000000000]),
+        &recent_norm_time,
+    );
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_12000000000000, 1_01000000000000]),
+        &(recent_norm_time - 300),
+    );
+    // outlier round
+    oracle_2.set_price(
+        &Vec::from_array(&e, [5_0000000, 0_0000001]),
+        &recent_norm_time,
+    );
+
+    let price_0 = oracle_aggregator_client
+        .lastprice(&Asset::Stellar(asset_0))
+        .unwrap();
+    assert_eq!(price_0.price, 0_1200000);
+    assert_eq!(price_0.timestamp, recent_norm_time - 300);
+
+    let price_1 = oracle_aggregator_client
+        .lastprice(&Asset::Stellar(asset_1))
+        .unwrap();
+    assert_eq!(price_1.price, 1_0000000);
+    assert_eq!(price_1.timestamp, recent_norm_time);
+}
+
+#[test]
+fn test_lastprice_median_drops_stale_sources() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let oracle_0 = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let oracle_1 = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let oracle_2 = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let oracles = vec![
+        &e,
+        oracle_0.address.clone(),
+        oracle_1.address.clone(),
+        oracle_2.address.clone(),
+    ];
+    let assets = create_asset_configs(&e, &oracles, &vec![&e, asset_0.clone(), asset_1.clone()]);
+    let (_, oracle_aggregator_client) = create_oracle_aggregator(
+        &e,
+        &Address::generate(&e),
+        &oracles,
+        &usdc,
+        &7,
+        &900,
+        &assets,
+        &None,
+    );
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    oracle_0.set_price(
+        &Vec::from_array(&e, [0_110000000, 1_000000000]),
+        &recent_norm_time,
+    );
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_120000000, 1_010000000]),
+        &(recent_norm_time - 300),
+    );
+    // stale source
+    oracle_2.set_price(
+        &Vec::from_array(&e, [9_000000000, 9_000000000]),
+        &(recent_norm_time - 1200),
+    );
+    oracle_2.set_price(&vec![&e], &recent_norm_time);
+
+    // median of the two fresh sources
+    let price_0 = oracle_aggregator_client
+        .lastprice(&Asset::Stellar(asset_0.clone()))
+        .unwrap();
+    assert_eq!(price_0.price, 0_1150000);
+    assert_eq!(price_0.timestamp, recent_norm_time - 300);
+
+    // all sources stale
+    e.jump(240);
+    let price_0 = oracle_aggregator_client.lastprice(&Asset::Stellar(asset_0));
+    assert!(price_0.is_none());
+}
+
+#[test]
+fn test_lastprice_median_drops_non_positive_prices() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let oracle_0 = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let oracle_1 = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let oracles = vec![&e, oracle_0.address.clone(), oracle_1.address.clone()];
+    let assets = create_asset_configs(&e, &oracles, &vec![&e, asset_0.clone(), asset_1.clone()]);
+    let (_, oracle_aggregator_client) = create_oracle_aggregator(
+        &e,
+        &Address::generate(&e),
+        &oracles,
+        &usdc,
+        &7,
+        &900,
+        &assets,
+        &None,
+    );
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    oracle_0.set_price(&Vec::from_array(&e, [0, -0_100000000]), &recent_norm_time);
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_110000000, 0_110000000]),
+        &recent_norm_time,
+    );
+
+    // the zero and negative prices are dropped before the median, like stale prices
+    let prices = oracle_aggregator_client.lastprices(&vec![
+        &e,
+        Asset::Stellar(asset_0.clone()),
+        Asset::Stellar(asset_1.clone()),
+    ]);
+    assert_eq!(prices.get_unchecked(0).unwrap().price, 0_1100000);
+    assert_eq!(prices.get_unchecked(1).unwrap().price, 0_1100000);
+    let detail = oracle_aggregator_client
+        .lastprice_detail(&Asset::Stellar(asset_0.clone()))
+        .unwrap();
+    assert_eq!(detail.agreed, 1);
+    assert_eq!(detail.oracle_id, Some(oracle_1.address.clone()));
+    let price_at = oracle_aggregator_client
+        .price(&Asset::Stellar(asset_1), &recent_norm_time)
+        .unwrap();
+    assert_eq!(price_at.price, 0_1100000);
+
+    // without a positive price, there is no price
+    oracle_1.set_price(&Vec::from_array(&e, [0, 0]), &recent_norm_time);
+    assert!(oracle_aggregator_client
+        .lastprice(&Asset::Stellar(asset_0))
+        .is_none());
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #101)")]
+fn test_lastprice_asset_without_config() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_110000000, 1_000000000]),
+        &e.ledger().timestamp(),
+    );
+
+    oracle_aggregator_client.lastprice(&Asset::Stellar(Address::generate(&e)));
+}
+
+#[test]
+fn test_lastprice_routes_assets_to_their_sources() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+    let dex_asset_0 = Address::generate(&e);
+
+    let dex_oracle = create_mock_oracle(&e, &usdc, &dex_asset_0, &asset_1, &14, &300);
+    let cex_oracle = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let (_, oracle_aggregator_client) = create_oracle_aggregator(
+        &e,
+        &Address::generate(&e),
+        &vec![&e, dex_oracle.address.clone(), cex_oracle.address.clone()],
+        &usdc,
+        &7,
+        &900,
+        &vec![
+            &e,
+            (
+                Asset::Stellar(asset_0.clone()),
+                AssetConfig {
+                    sources: vec![
+                        &e,
+                        AssetSource {
+                            oracle_id: dex_oracle.address.clone(),
+                            asset: Asset::Stellar(dex_asset_0.clone()),
+                        },
+                    ],
+                    fallbacks: vec![&e],
+                    max_age: None,
+                },
+            ),
+            (
+                Asset::Stellar(asset_1.clone()),
+                AssetConfig {
+                    sources: vec![
+                        &e,
+                        AssetSource {
+                            oracle_id: cex_oracle.address.clone(),
+                            asset: Asset::Stellar(asset_1.clone()),
+                        },
+                    ],
+                    fallbacks: vec![&e],
+                    max_age: None,
+                },
+            ),
+        ],
+        &None,
+    );
+
+    dex_oracle.set_price(
+        &Vec::from_array(&e, [0_11000000000000, 9_00000000000000]),
+        &e.ledger().timestamp(),
+    );
+    cex_oracle.set_price(
+        &Vec::from_array(&e, [9_000000000, 1_010000000]),
+        &e.ledger().timestamp(),
+    );
+
+    let price_0 = oracle_aggregator_client
+        .lastprice(&Asset::Stellar(asset_0))
+        .unwrap();
+    assert_eq!(price_0.price, 0_1100000);
+
+    let price_1 = oracle_aggregator_client
+        .lastprice(&Asset::Stellar(asset_1))
+        .unwrap();
+    assert_eq!(price_1.price, 1_0100000);
+}
+
+#[test]
+fn test_lastprice_uses_asset_max_age() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let oracle = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let oracles = vec![&e, oracle.address.clone()];
+    let mut assets =
+        create_asset_configs(&e, &oracles, &vec![&e, asset_0.clone(), asset_1.clone()]);
+    let (asset, mut asset_config) = assets.get_unchecked(0);
+    asset_config.max_age = Some(360);
+    assets.set(0, (asset, asset_config));
+    let (_, oracle_aggregator_client) = create_oracle_aggregator(
+        &e,
+        &Address::generate(&e),
+        &oracles,
+        &usdc,
+        &7,
+        &900,
+        &assets,
+        &None,
+    );
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    oracle.set_price(
+        &Vec::from_array(&e, [0_120000000, 1_010000000]),
+        &(recent_norm_time - 600),
+    );
+    oracle.set_price(&vec![&e], &(recent_norm_time - 300));
+    oracle.set_price(&vec![&e], &recent_norm_time);
+
+    // asset 0 uses its own max age
+    let price_0 = oracle_aggregator_client.lastprice(&Asset::Stellar(asset_0));
+    assert!(price_0.is_none());
+
+    // asset 1 uses the global max age
+    let price_1 = oracle_aggregator_client
+        .lastprice(&Asset::Stellar(asset_1))
+        .unwrap();
+    assert_eq!(price_1.price, 1_0100000);
+    assert_eq!(price_1.timestamp, recent_norm_time - 600);
+}
@@ -0,0 +1,155 @@
+#![cfg(test)]
+use crate::{
+    testutils::{
+        create_asset_configs, create_mock_oracle, create_oracle_aggregator,
+        setup_default_aggregator, EnvTestUtils,
+    },
+    types::PriceMethod,
+};
+use sep_40_oracle::Asset;
+use soroban_sdk::{testutils::Address as _, vec, Address, Env, Vec};
+
+#[test]
+fn test_lastprice_detail() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_110000000, 1_000000000]),
+        &recent_norm_time,
+    );
+    e.jump(12);
+
+    let detail = oracle_aggregator_client
+        .lastprice_detail(&Asset::Stellar(asset_0.clone()))
+        .unwrap();
+    assert_eq!(detail.price.price, 0_1100000);
+    assert_eq!(detail.price.timestamp, recent_norm_time);
+    assert_eq!(detail.oracle_id, Some(oracle_1.address.clone()));
+    assert_eq!(detail.method, PriceMethod::LastPrice);
+    assert_eq!(detail.steps, 0);
+    assert_eq!(detail.age, 60);
+    assert_eq!(detail.agreed, 1);
+
+    // the price is the same as the price returned by `lastprice`
+    let price = oracle_aggregator_client
+        .lastprice(&Asset::Stellar(asset_0))
+        .unwrap();
+    assert_eq!(price.price, detail.price.price);
+    assert_eq!(price.timestamp, detail.price.timestamp);
+
+    // USDC is priced at 1 and is not answered by a source oracle
+    let detail = oracle_aggregator_client
+        .lastprice_detail(&Asset::Stellar(usdc))
+        .unwrap();
+    assert_eq!(detail.price.price, 1_0000000);
+    assert_eq!(detail.oracle_id, None);
+    assert_eq!(detail.method, PriceMethod::LastPrice);
+    assert_eq!(detail.steps, 0);
+    assert_eq!(detail.age, 0);
+    assert_eq!(detail.agreed, 0);
+}
+
+#[test]
+fn test_lastprice_detail_median_retry() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let oracle_0 = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let oracle_1 = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let oracle_2 = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let oracles = vec![
+        &e,
+        oracle_0.address.clone(),
+        oracle_1.address.clone(),
+        oracle_2.address.clone(),
+    ];
+    let (_, oracle_aggregator_client) = create_oracle_aggregator(
+        &e,
+        &Address::generate(&e),
+        &oracles,
+        &usdc,
+        &7,
+        &900,
+        &create_asset_configs(&e, &oracles, &vec![&e, asset_0.clone(), asset_1.clone()]),
+        &None,
+    );
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    oracle_0.set_price(
+        &Vec::from_array(&e, [0_100000000, 1_000000000]),
+        &recent_norm_time,
+    );
+    // the median price is only found by the `price` retry, two resolution steps back
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_110000000, 1_000000000]),
+        &(recent_norm_time - 600),
+    );
+    oracle_1.set_price(&vec![&e], &(recent_norm_time - 300));
+    oracle_1.set_price(&vec![&e], &recent_norm_time);
+    oracle_2.set_price(
+        &Vec::from_array(&e, [0_120000000, 1_000000000]),
+        &recent_norm_time,
+    );
+
+    let detail = oracle_aggregator_client
+        .lastprice_detail(&Asset::Stellar(asset_0.clone()))
+        .unwrap();
+    assert_eq!(detail.price.price, 0_1100000);
+    assert_eq!(detail.price.timestamp, recent_norm_time - 600);
+    assert_eq!(detail.oracle_id, Some(oracle_1.address.clone()));
+    assert_eq!(detail.method, PriceMethod::Price);
+    assert_eq!(detail.steps, 2);
+    assert_eq!(detail.age, 600);
+    assert_eq!(detail.agreed, 3);
+
+    // the oracle that answered is the one that reported the median price
+    oracle_2.set_price(
+        &Vec::from_array(&e, [0_105000000, 1_000000000]),
+        &recent_norm_time,
+    );
+    let detail = oracle_aggregator_client
+        .lastprice_detail(&Asset::Stellar(asset_0))
+        .unwrap();
+    assert_eq!(detail.price.price, 0_1050000);
+    assert_eq!(detail.oracle_id, Some(oracle_2.address.clone()));
+    assert_eq!(detail.method, PriceMethod::LastPrice);
+    assert_eq!(detail.steps, 0);
+    assert_eq!(detail.age, 0);
+    assert_eq!(detail.agreed, 3);
+}
+
+#[test]
+fn test_lastprice_detail_none() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_110000000, 1_000000000]),
+        &recent_norm_time,
+    );
+    e.jump(200);
+
+    let detail = oracle_aggregator_client.lastprice_detail(&Asset::Stellar(asset_0));
+    assert!(detail.is_none());
+}
@@ -0,0 +1,131 @@
+#![cfg(test)]
+use crate::testutils::{setup_default_aggregator, EnvTestUtils};
+use sep_40_oracle::Asset;
+use soroban_sdk::{testutils::Address as _, vec, Address, Env, Vec};
+
+#[test]
+fn test_lastprices() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_120000000, 1_010000000]),
+        &(recent_norm_time - 300),
+    );
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_110000000, 1_000000000]),
+        &recent_norm_time,
+    );
+
+    let prices = oracle_aggregator_client.lastprices(&vec![
+        &e,
+        Asset::Stellar(asset_1.clone()),
+        Asset::Stellar(usdc.clone()),
+        Asset::Stellar(asset_0.clone()),
+    ]);
+    assert_eq!(prices.len(), 3);
+    let price_1 = prices.get_unchecked(0).unwrap();
+    assert_eq!(price_1.price, 1_0000000);
+    assert_eq!(price_1.timestamp, recent_norm_time);
+    let price_usdc = prices.get_unchecked(1).unwrap();
+    assert_eq!(price_usdc.price, 1_0000000);
+    assert_eq!(price_usdc.timestamp, e.ledger().timestamp());
+    let price_0 = prices.get_unchecked(2).unwrap();
+    assert_eq!(price_0.price, 0_1100000);
+    assert_eq!(price_0.timestamp, recent_norm_time);
+
+    assert_eq!(oracle_aggregator_client.lastprices(&vec![&e]).len(), 0);
+}
+
+#[test]
+fn test_lastprices_missing_price() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_110000000, 1_000000000]),
+        &(recent_norm_time - 1200),
+    );
+
+    let prices = oracle_aggregator_client.lastprices(&vec![
+        &e,
+        Asset::Stellar(asset_0.clone()),
+        Asset::Stellar(usdc.clone()),
+    ]);
+    assert_eq!(prices.len(), 2);
+    assert!(prices.get_unchecked(0).is_none());
+    assert!(prices.get_unchecked(1).is_some());
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #101)")]
+fn test_lastprices_asset_without_config() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_110000000, 1_000000000]),
+        &e.ledger().timestamp(),
+    );
+
+    oracle_aggregator_client.lastprices(&vec![
+        &e,
+        Asset::Stellar(asset_0),
+        Asset::Stellar(Address::generate(&e)),
+    ]);
+}
+
+#[test]
+fn test_lastprices_reads_less_than_lastprice() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_110000000, 1_000000000]),
+        &e.ledger().timestamp(),
+    );
+
+    oracle_aggregator_client.lastprice(&Asset::Stellar(asset_0.clone()));
+    let read_entries_0 = e.cost_estimate().resources().read_entries;
+    oracle_aggregator_client.lastprice(&Asset::Stellar(asset_1.clone()));
+    let read_entries_1 = e.cost_estimate().resources().read_entries;
+
+    let prices = oracle_aggregator_client.lastprices(&vec![
+        &e,
+        Asset::Stellar(asset_0),
+        Asset::Stellar(asset_1),
+    ]);
+    let read_entries_batch = e.cost_estimate().resources().read_entries;
+    assert_eq!(prices.get_unchecked(0).unwrap().price, 0_1100000);
+    assert_eq!(prices.get_unchecked(1).unwrap().price, 1_0000000);
+    assert!(read_entries_batch < read_entries_0 + read_entries_1);
+}
@@ -0,0 +1,316 @@
+#![cfg(test)]
+use crate::{
+    testutils::{assert_assets_equal, setup_default_aggregator, EnvTestUtils},
+    types::{AssetConfig, AssetSource},
+};
+use sep_40_oracle::{
+    testutils::{Asset as MockAsset, MockPriceOracleClient, MockPriceOracleWASM},
+    Asset,
+};
+use soroban_sdk::{
+    testutils::{Address as _, AuthorizedFunction, AuthorizedInvocation},
+    vec, Address, Env, IntoVal, Symbol, Vec,
+};
+
+/// Deploy a mock oracle that reports the price of a token in another asset
+fn create_token_oracle<'a>(e: &Env, base: &Address, token: &Address) -> MockPriceOracleClient<'a> {
+    let oracle_id = Address::generate(e);
+    e.register_at(&oracle_id, MockPriceOracleWASM, ());
+    let oracle = MockPriceOracleClient::new(e, &oracle_id);
+    oracle.set_data(
+        &Address::generate(e),
+        &MockAsset::Stellar(base.clone()),
+        &vec![e, MockAsset::Stellar(token.clone())],
+        &14,
+        &300,
+    );
+    oracle
+}
+
+#[test]
+fn test_asset_path() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let xlm = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+    let token = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) = setup_default_aggregator(&e, &usdc, &xlm, &asset_1);
+    let xlm_oracle = create_token_oracle(&e, &xlm, &token);
+
+    let path = Some(vec![
+        &e,
+        AssetSource {
+            oracle_id: xlm_oracle.address.clone(),
+            asset: Asset::Stellar(token.clone()),
+        },
+        AssetSource {
+            oracle_id: oracle_1.address.clone(),
+            asset: Asset::Stellar(xlm.clone()),
+        },
+    ]);
+    oracle_aggregator_client.set_asset_path(&Asset::Stellar(token.clone()), &path);
+    assert_eq!(
+        e.auths()[0],
+        (
+            oracle_aggregator_client.admin(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    oracle_aggregator_client.address.clone(),
+                    Symbol::new(&e, "set_asset_path"),
+                    vec![
+                        &e,
+                        Asset::Stellar(token.clone()).into_val(&e),
+                        path.into_val(&e)
+                    ]
+                )),
+                sub_invocations: std::vec![]
+            }
+        )
+    );
+    let legs = oracle_aggregator_client
+        .asset_path(&Asset::Stellar(token.clone()))
+        .unwrap();
+    assert_eq!(legs.len(), 2);
+    assert_eq!(legs.get_unchecked(0).oracle.oracle_id, xlm_oracle.address);
+    assert_eq!(legs.get_unchecked(0).oracle.decimals, 14);
+    assert_eq!(legs.get_unchecked(1).oracle.oracle_id, oracle_1.address);
+    assert_eq!(legs.get_unchecked(1).oracle.decimals, 9);
+
+    // path assets are listed by `assets`
+    let path_assets = oracle_aggregator_client.path_assets();
+    assert_eq!(path_assets.len(), 1);
+    assert!(assert_assets_equal(
+        path_assets.get_unchecked(0),
+        Asset::Stellar(token.clone())
+    ));
+    let assets = oracle_aggregator_client.assets();
+    assert_eq!(assets.len(), 4);
+    assert!(assert_assets_equal(
+        assets.get_unchecked(2),
+        Asset::Stellar(token.clone())
+    ));
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    xlm_oracle.set_price(
+        &Vec::from_array(&e, [2_40000000000000]),
+        &(recent_norm_time - 300),
+    );
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_100000000, 1_000000000]),
+        &(recent_norm_time - 300),
+    );
+    xlm_oracle.set_price(&Vec::from_array(&e, [2_50000000000000]), &recent_norm_time);
+    oracle_1.set_price(&vec![&e], &recent_norm_time);
+
+    // the legs are multiplied, and the oldest timestamp is used
+    let sourced_price = oracle_aggregator_client
+        .lastprice_with_source(&Asset::Stellar(token.clone()))
+        .unwrap();
+    assert_eq!(sourced_price.price.price, 0_2500000);
+    assert_eq!(sourced_price.price.timestamp, recent_norm_time - 300);
+    assert_eq!(
+        sourced_price.sources,
+        vec![&e, xlm_oracle.address.clone(), oracle_1.address.clone()]
+    );
+
+    let price = oracle_aggregator_client
+        .price(&Asset::Stellar(token.clone()), &(recent_norm_time - 300))
+        .unwrap();
+    assert_eq!(price.price, 0_2400000);
+    assert_eq!(price.timestamp, recent_norm_time - 300);
+
+    // rounds of the first leg are only priced if every leg reported them
+    let prices = oracle_aggregator_client
+        .prices(&Asset::Stellar(token.clone()), &2)
+        .unwrap();
+    assert_eq!(prices.len(), 1);
+    assert_eq!(prices.get_unchecked(0).price, 0_2400000);
+    assert_eq!(prices.get_unchecked(0).timestamp, recent_norm_time - 300);
+
+    // each leg must have a price at most `max_age` old
+    e.jump(200);
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_110000000, 1_000000000]),
+        &(recent_norm_time + 900),
+    );
+    let price = oracle_aggregator_client.lastprice(&Asset::Stellar(token.clone()));
+    assert!(price.is_none());
+
+    // removing the path stops listing the asset
+    oracle_aggregator_client.set_asset_path(&Asset::Stellar(token.clone()), &None);
+    assert!(oracle_aggregator_client
+        .asset_path(&Asset::Stellar(token.clone()))
+        .is_none());
+    assert_eq!(oracle_aggregator_client.path_assets().len(), 0);
+    assert_eq!(oracle_aggregator_client.assets().len(), 3);
+    let result = oracle_aggregator_client.try_lastprice(&Asset::Stellar(token));
+    assert!(result.is_err());
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #115)")]
+fn test_asset_path_not_in_usdc() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let xlm = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+    let token = Address::generate(&e);
+
+    let (oracle_aggregator_client, _) = setup_default_aggregator(&e, &usdc, &xlm, &asset_1);
+    let xlm_oracle = create_token_oracle(&e, &xlm, &token);
+
+    oracle_aggregator_client.set_asset_path(
+        &Asset::Stellar(token.clone()),
+        &Some(vec![
+            &e,
+            AssetSource {
+                oracle_id: xlm_oracle.address.clone(),
+                asset: Asset::Stellar(token),
+            },
+        ]),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #115)")]
+fn test_asset_path_broken_chain() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let xlm = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+    let token = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) = setup_default_aggregator(&e, &usdc, &xlm, &asset_1);
+    let xlm_oracle = create_token_oracle(&e, &xlm, &token);
+
+    // the first leg is quoted in XLM, but the second leg reports asset 1
+    oracle_aggregator_client.set_asset_path(
+        &Asset::Stellar(token.clone()),
+        &Some(vec![
+            &e,
+            AssetSource {
+                oracle_id: xlm_oracle.address.clone(),
+                asset: Asset::Stellar(token),
+            },
+            AssetSource {
+                oracle_id: oracle_1.address.clone(),
+                asset: Asset::Stellar(asset_1),
+            },
+        ]),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #115)")]
+fn test_asset_path_empty() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let xlm = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, _) = setup_default_aggregator(&e, &usdc, &xlm, &asset_1);
+
+    oracle_aggregator_client
+        .set_asset_path(&Asset::Stellar(Address::generate(&e)), &Some(vec![&e]));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #115)")]
+fn test_asset_path_with_asset_config() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let xlm = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) = setup_default_aggregator(&e, &usdc, &xlm, &asset_1);
+
+    oracle_aggregator_client.set_asset_path(
+        &Asset::Stellar(asset_1.clone()),
+        &Some(vec![
+            &e,
+            AssetSource {
+                oracle_id: oracle_1.address.clone(),
+                asset: Asset::Stellar(asset_1),
+            },
+        ]),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #115)")]
+fn test_set_asset_config_path_asset() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let xlm = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+    let token = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) = setup_default_aggregator(&e, &usdc, &xlm, &asset_1);
+    let xlm_oracle = create_token_oracle(&e, &xlm, &token);
+    oracle_aggregator_client.set_asset_path(
+        &Asset::Stellar(token.clone()),
+        &Some(vec![
+            &e,
+            AssetSource {
+                oracle_id: xlm_oracle.address.clone(),
+                asset: Asset::Stellar(token.clone()),
+            },
+            AssetSource {
+                oracle_id: oracle_1.address.clone(),
+                asset: Asset::Stellar(xlm.clone()),
+            },
+        ]),
+    );
+
+    oracle_aggregator_client.set_asset_config(
+        &Asset::Stellar(token),
+        &AssetConfig {
+            sources: vec![
+                &e,
+                AssetSource {
+                    oracle_id: oracle_1.address.clone(),
+                    asset: Asset::Stellar(xlm),
+                },
+            ],
+            fallbacks: Vec::new(&e),
+            max_age: None,
+        },
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Auth, InvalidAction)")]
+fn test_set_asset_path_requires_admin() {
+    let e = Env::default();
+    e.set_default_info();
+    let usdc = Address::generate(&e);
+    let xlm = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) = setup_default_aggregator(&e, &usdc, &xlm, &asset_1);
+
+    e.set_auths(&[]);
+    oracle_aggregator_client.set_asset_path(
+        &Asset::Stellar(Address::generate(&e)),
+        &Some(vec![
+            &e,
+            AssetSource {
+                oracle_id: oracle_1.address.clone(),
+                asset: Asset::Stellar(xlm),
+            },
+        ]),
+    );
+}
@@ -0,0 +1,258 @@
+#![cfg(test)]
+use crate::testutils::{assert_assets_equal, setup_default_aggregator, EnvTestUtils};
+use sep_40_oracle::Asset;
+use soroban_sdk::{
+    testutils::{Address as _, AuthorizedFunction, AuthorizedInvocation, MockAuth, MockAuthInvoke},
+    vec, Address, Env, IntoVal, Symbol, Vec,
+};
+
+#[test]
+fn test_pause_asset() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let guardian = Address::generate(&e);
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+    let admin = oracle_aggregator_client.admin();
+
+    oracle_aggregator_client.set_guardian(&guardian);
+    assert_eq!(
+        e.auths()[0],
+        (
+            admin.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    oracle_aggregator_client.address.clone(),
+                    Symbol::new(&e, "set_guardian"),
+                    vec![&e, guardian.to_val()]
+                )),
+                sub_invocations: std::vec![]
+            }
+        )
+    );
+    assert_eq!(oracle_aggregator_client.guardian(), Some(guardian.clone()));
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_110000000, 1_000000000]),
+        &recent_norm_time,
+    );
+
+    let paused_asset = Some(Asset::Stellar(asset_0.clone()));
+    oracle_aggregator_client.pause(&paused_asset);
+    assert_eq!(
+        e.auths()[0],
+        (
+            guardian.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    oracle_aggregator_client.address.clone(),
+                    Symbol::new(&e, "pause"),
+                    vec![&e, paused_asset.into_val(&e)]
+                )),
+                sub_invocations: std::vec![]
+            }
+        )
+    );
+    assert!(oracle_aggregator_client.is_paused(&Asset::Stellar(asset_0.clone())));
+    assert!(!oracle_aggregator_client.paused());
+
+    assert!(oracle_aggregator_client
+        .lastprice(&Asset::Stellar(asset_0.clone()))
+        .is_none());
+    assert!(oracle_aggregator_client
+        .price(&Asset::Stellar(asset_0.clone()), &recent_norm_time)
+        .is_none());
+    assert!(oracle_aggregator_client
+        .prices(&Asset::Stellar(asset_0.clone()), &1)
+        .is_none());
+    let prices = oracle_aggregator_client.lastprices(&vec![
+        &e,
+        Asset::Stellar(asset_0.clone()),
+        Asset::Stellar(asset_1.clone()),
+    ]);
+    assert!(prices.get_unchecked(0).is_none());
+    assert_eq!(prices.get_unchecked(1).unwrap().price, 1_0000000);
+    let price_1 = oracle_aggregator_client
+        .lastprice(&Asset::Stellar(asset_1.clone()))
+        .unwrap();
+    assert_eq!(price_1.price, 1_0000000);
+
+    let assets = oracle_aggregator_client.assets();
+    assert_eq!(assets.len(), 2);
+    assert!(assert_assets_equal(
+        assets.get_unchecked(0),
+        Asset::Stellar(asset_1.clone())
+    ));
+    assert!(assert_assets_equal(
+        assets.get_unchecked(1),
+        Asset::Stellar(usdc.clone())
+    ));
+
+    oracle_aggregator_client.unpause(&paused_asset);
+    assert_eq!(
+        e.auths()[0],
+        (
+            admin.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    oracle_aggregator_client.address.clone(),
+                    Symbol::new(&e, "unpause"),
+                    vec![&e, paused_asset.into_val(&e)]
+                )),
+                sub_invocations: std::vec![]
+            }
+        )
+    );
+    assert!(!oracle_aggregator_client.is_paused(&Asset::Stellar(asset_0.clone())));
+    let price_0 = oracle_aggregator_client
+        .lastprice(&Asset::Stellar(asset_0))
+        .unwrap();
+    assert_eq!(price_0.price, 0_1100000);
+    assert_eq!(oracle_aggregator_client.assets().len(), 3);
+}
+
+#[test]
+fn test_pause_all() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let guardian = Address::generate(&e);
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+    oracle_aggregator_client.set_guardian(&guardian);
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_110000000, 1_000000000]),
+        &recent_norm_time,
+    );
+
+    oracle_aggregator_client.pause(&Some(Asset::Stellar(asset_1.clone())));
+    oracle_aggregator_client.pause(&None);
+    assert!(oracle_aggregator_client.paused());
+
+    assert!(oracle_aggregator_client
+        .lastprice(&Asset::Stellar(asset_0.clone()))
+        .is_none());
+    assert!(oracle_aggregator_client
+        .lastprice(&Asset::Stellar(usdc.clone()))
+        .is_none());
+    assert!(oracle_aggregator_client
+        .x_lastprice(
+            &Asset::Stellar(asset_0.clone()),
+            &Asset::Stellar(usdc.clone())
+        )
+        .is_none());
+    assert_eq!(oracle_aggregator_client.assets().len(), 0);
+
+    // lifting the global pause does not unpause assets paused individually
+    oracle_aggregator_client.unpause(&None);
+    assert!(!oracle_aggregator_client.paused());
+    assert!(oracle_aggregator_client
+        .lastprice(&Asset::Stellar(asset_1.clone()))
+        .is_none());
+    let price_0 = oracle_aggregator_client
+        .lastprice(&Asset::Stellar(asset_0))
+        .unwrap();
+    assert_eq!(price_0.price, 0_1100000);
+    assert_eq!(oracle_aggregator_client.assets().len(), 2);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #108)")]
+fn test_twap_paused() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let guardian = Address::generate(&e);
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, _) = setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+    oracle_aggregator_client.set_guardian(&guardian);
+    oracle_aggregator_client.pause(&None);
+
+    oracle_aggregator_client.twap(&Asset::Stellar(asset_0), &1);
+}
+
+#[test]
+#[should_panic(expected = "Error(Auth, InvalidAction)")]
+fn test_pause_requires_guardian() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let guardian = Address::generate(&e);
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, _) = setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+    oracle_aggregator_client.set_guardian(&guardian);
+
+    // the admin is not able to pause
+    let admin = oracle_aggregator_client.admin();
+    e.mock_auths(&[MockAuth {
+        address: &admin,
+        invoke: &MockAuthInvoke {
+            contract: &oracle_aggregator_client.address,
+            fn_name: "pause",
+            args: vec![&e, Option::<Asset>::None.into_val(&e)],
+            sub_invokes: &[],
+        },
+    }]);
+    oracle_aggregator_client.pause(&None);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #116)")]
+fn test_pause_without_guardian() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, _) = setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+
+    oracle_aggregator_client.pause(&None);
+}
+
+#[test]
+#[should_panic(expected = "Error(Auth, InvalidAction)")]
+fn test_unpause_requires_admin() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let guardian = Address::generate(&e);
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, _) = setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+    oracle_aggregator_client.set_guardian(&guardian);
+    oracle_aggregator_client.pause(&None);
+
+    // the guardian is not able to unpause
+    e.mock_auths(&[MockAuth {
+        address: &guardian,
+        invoke: &MockAuthInvoke {
+            contract: &oracle_aggregator_client.address,
+            fn_name: "unpause",
+            args: vec![&e, Option::<Asset>::None.into_val(&e)],
+            sub_invokes: &[],
+        },
+    }]);
+    oracle_aggregator_client.unpause(&None);
+}
@@ -0,0 +1,455 @@
+#![cfg(test)]
+use crate::{
+    testutils::{assert_assets_equal, setup_default_aggregator, EnvTestUtils},
+    types::{AssetConfig, AssetSource, PegFeed, PeggedAsset, PriceBounds},
+};
+use sep_40_oracle::Asset;
+use soroban_sdk::{
+    testutils::{Address as _, AuthorizedFunction, AuthorizedInvocation, Events},
+    vec, Address, Env, IntoVal, Symbol, Vec,
+};
+
+#[test]
+fn test_pegged_asset() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+    let stable = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+
+    let pegged = Some(PeggedAsset {
+        price: 1_0000000,
+        feed: PegFeed::Unchecked,
+        tolerance: 0,
+    });
+    oracle_aggregator_client.set_pegged_asset(&Asset::Stellar(stable.clone()), &pegged);
+    assert_eq!(
+        e.auths()[0],
+        (
+            oracle_aggregator_client.admin(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    oracle_aggregator_client.address.clone(),
+                    Symbol::new(&e, "set_pegged_asset"),
+                    vec![
+                        &e,
+                        Asset::Stellar(stable.clone()).into_val(&e),
+                        pegged.into_val(&e)
+                    ]
+                )),
+                sub_invocations: std::vec![]
+            }
+        )
+    );
+    let pegged = oracle_aggregator_client
+        .pegged_asset(&Asset::Stellar(stable.clone()))
+        .unwrap();
+    assert_eq!(pegged.price, 1_0000000);
+    assert!(matches!(pegged.feed, PegFeed::Unchecked));
+
+    // pegged assets are listed by `assets`
+    let pegged_assets = oracle_aggregator_client.pegged_assets();
+    assert_eq!(pegged_assets.len(), 1);
+    assert!(assert_assets_equal(
+        pegged_assets.get_unchecked(0),
+        Asset::Stellar(stable.clone())
+    ));
+    let assets = oracle_aggregator_client.assets();
+    assert_eq!(assets.len(), 4);
+    assert!(assert_assets_equal(
+        assets.get_unchecked(2),
+        Asset::Stellar(stable.clone())
+    ));
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_110000000, 1_000000000]),
+        &recent_norm_time,
+    );
+
+    // the fixed price is reported like the price of USDC
+    let price = oracle_aggregator_client
+        .lastprice(&Asset::Stellar(stable.clone()))
+        .unwrap();
+    assert_eq!(price.price, 1_0000000);
+    assert_eq!(price.timestamp, e.ledger().timestamp());
+
+    let price = oracle_aggregator_client
+        .price(&Asset::Stellar(stable.clone()), &(recent_norm_time - 300))
+        .unwrap();
+    assert_eq!(price.price, 1_0000000);
+    assert_eq!(price.timestamp, recent_norm_time - 300);
+
+    let prices = oracle_aggregator_client
+        .prices(&Asset::Stellar(stable.clone()), &2)
+        .unwrap();
+    assert_eq!(prices.len(), 2);
+    assert_eq!(prices.get_unchecked(0).timestamp, recent_norm_time);
+    assert_eq!(prices.get_unchecked(1).timestamp, recent_norm_time - 300);
+
+    let x_price = oracle_aggregator_client
+        .x_lastprice(
+            &Asset::Stellar(asset_0.clone()),
+            &Asset::Stellar(stable.clone()),
+        )
+        .unwrap();
+    assert_eq!(x_price.price, 0_1100000);
+
+    // removing the pegged asset stops listing it
+    oracle_aggregator_client.set_pegged_asset(&Asset::Stellar(stable.clone()), &None);
+    assert!(oracle_aggregator_client
+        .pegged_asset(&Asset::Stellar(stable.clone()))
+        .is_none());
+    assert_eq!(oracle_aggregator_client.pegged_assets().len(), 0);
+    assert_eq!(oracle_aggregator_client.assets().len(), 3);
+    let result = oracle_aggregator_client.try_lastprice(&Asset::Stellar(stable));
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_pegged_asset_with_feed() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+    let stable = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+    // the feed reports the price of the pegged asset as asset 1
+    oracle_aggregator_client.set_pegged_asset(
+        &Asset::Stellar(stable.clone()),
+        &Some(PeggedAsset {
+            price: 1_0000000,
+            feed: PegFeed::Source(AssetSource {
+                oracle_id: oracle_1.address.clone(),
+                asset: Asset::Stellar(asset_1.clone()),
+            }),
+            tolerance: 100,
+        }),
+    );
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_110000000, 0_995000000]),
+        &(recent_norm_time - 300),
+    );
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_110000000, 0_980000000]),
+        &recent_norm_time,
+    );
+
+    // within the tolerance band, the fixed price uses the timestamp of the feed
+    let price = oracle_aggregator_client
+        .price(&Asset::Stellar(stable.clone()), &(recent_norm_time - 300))
+        .unwrap();
+    assert_eq!(price.price, 1_0000000);
+    assert_eq!(price.timestamp, recent_norm_time - 300);
+
+    // outside of the tolerance band, the asset is not priced
+    let price = oracle_aggregator_client.lastprice(&Asset::Stellar(stable.clone()));
+    assert!(price.is_none());
+    let events = e.events().all();
+    let event = events.get_unchecked(events.len() - 1);
+    assert_eq!(
+        vec![&e, event],
+        vec![
+            &e,
+            (
+                oracle_aggregator_client.address.clone(),
+                (
+                    Symbol::new(&e, "price_depeg"),
+                    Asset::Stellar(stable.clone())
+                )
+                    .into_val(&e),
+                (oracle_1.address.clone(), 1_0000000i128, 0_9800000i128).into_val(&e)
+            )
+        ]
+    );
+
+    // depegged rounds are skipped
+    let prices = oracle_aggregator_client
+        .prices(&Asset::Stellar(stable.clone()), &2)
+        .unwrap();
+    assert_eq!(prices.len(), 1);
+    assert_eq!(prices.get_unchecked(0).timestamp, recent_norm_time - 300);
+
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_110000000, 1_004000000]),
+        &(recent_norm_time + 300),
+    );
+    e.jump(60);
+    let sourced_price = oracle_aggregator_client
+        .lastprice_with_source(&Asset::Stellar(stable.clone()))
+        .unwrap();
+    assert_eq!(sourced_price.price.price, 1_0000000);
+    assert_eq!(sourced_price.price.timestamp, recent_norm_time + 300);
+    assert_eq!(sourced_price.sources, vec![&e, oracle_1.address.clone()]);
+
+    // the feed must have a recent price
+    e.jump(200);
+    let price = oracle_aggregator_client.lastprice(&Asset::Stellar(stable));
+    assert!(price.is_none());
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #114)")]
+fn test_pegged_asset_usdc() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, _) = setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+
+    oracle_aggregator_client.set_pegged_asset(
+        &Asset::Stellar(usdc),
+        &Some(PeggedAsset {
+            price: 1_0000000,
+            feed: PegFeed::Unchecked,
+            tolerance: 0,
+        }),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #114)")]
+fn test_pegged_asset_with_asset_config() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, _) = setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+
+    oracle_aggregator_client.set_pegged_asset(
+        &Asset::Stellar(asset_0),
+        &Some(PeggedAsset {
+            price: 1_0000000,
+            feed: PegFeed::Unchecked,
+            tolerance: 0,
+        }),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #114)")]
+fn test_pegged_asset_zero_price() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, _) = setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+
+    oracle_aggregator_client.set_pegged_asset(
+        &Asset::Stellar(Address::generate(&e)),
+        &Some(PeggedAsset {
+            price: 0,
+            feed: PegFeed::Unchecked,
+            tolerance: 0,
+        }),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #101)")]
+fn test_pegged_asset_feed_not_source_oracle() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, _) = setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+
+    oracle_aggregator_client.set_pegged_asset(
+        &Asset::Stellar(Address::generate(&e)),
+        &Some(PeggedAsset {
+            price: 1_0000000,
+            feed: PegFeed::Source(AssetSource {
+                oracle_id: Address::generate(&e),
+                asset: Asset::Stellar(asset_1),
+            }),
+            tolerance: 100,
+        }),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #114)")]
+fn test_set_asset_config_pegged_asset() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+    let stable = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+    oracle_aggregator_client.set_pegged_asset(
+        &Asset::Stellar(stable.clone()),
+        &Some(PeggedAsset {
+            price: 1_0000000,
+            feed: PegFeed::Unchecked,
+            tolerance: 0,
+        }),
+    );
+
+    oracle_aggregator_client.set_asset_config(
+        &Asset::Stellar(stable),
+        &AssetConfig {
+            sources: vec![
+                &e,
+                AssetSource {
+                    oracle_id: oracle_1.address.clone(),
+                    asset: Asset::Stellar(asset_0),
+                },
+            ],
+            fallbacks: Vec::new(&e),
+            max_age: None,
+        },
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Auth, InvalidAction)")]
+fn test_set_pegged_asset_requires_admin() {
+    let e = Env::default();
+    e.set_default_info();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, _) = setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+
+    e.set_auths(&[]);
+    oracle_aggregator_client.set_pegged_asset(
+        &Asset::Stellar(Address::generate(&e)),
+        &Some(PeggedAsset {
+            price: 1_0000000,
+            feed: PegFeed::Unchecked,
+            tolerance: 0,
+        }),
+    );
+}
+
+#[test]
+fn test_set_decimals_rescales_stored_prices() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+    let eurc = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+    oracle_aggregator_client.set_pegged_asset(
+        &Asset::Stellar(eurc.clone()),
+        &Some(PeggedAsset {
+            price: 1_0800000,
+            feed: PegFeed::Unchecked,
+            tolerance: 0,
+        }),
+    );
+    oracle_aggregator_client.set_price_bounds(
+        &Asset::Stellar(asset_0.clone()),
+        &Some(PriceBounds {
+            min: 0_1000000,
+            max: 0_2000000,
+        }),
+    );
+    oracle_aggregator_client.set_grace_period(&600);
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_110000000, 1_000000000]),
+        &recent_norm_time,
+    );
+    // cache the last known good price of asset 0
+    let price = oracle_aggregator_client
+        .lastprice(&Asset::Stellar(asset_0.clone()))
+        .unwrap();
+    assert_eq!(price.price, 0_1100000);
+
+    oracle_aggregator_client.set_decimals(&9);
+
+    let pegged = oracle_aggregator_client
+        .pegged_asset(&Asset::Stellar(eurc.clone()))
+        .unwrap();
+    assert_eq!(pegged.price, 1_080000000);
+    let price = oracle_aggregator_client
+        .lastprice(&Asset::Stellar(eurc))
+        .unwrap();
+    assert_eq!(price.price, 1_080000000);
+
+    let bounds = oracle_aggregator_client
+        .price_bounds(&Asset::Stellar(asset_0.clone()))
+        .unwrap();
+    assert_eq!(bounds.min, 0_100000000);
+    assert_eq!(bounds.max, 0_200000000);
+
+    // the cached price is served in the new decimals once the price is stale
+    e.jump(200);
+    let grace_price = oracle_aggregator_client
+        .lastprice_with_grace(&Asset::Stellar(asset_0))
+        .unwrap();
+    assert!(grace_price.stale);
+    assert_eq!(grace_price.price.price, 0_110000000);
+    assert_eq!(grace_price.price.timestamp, recent_norm_time);
+}
+
+#[test]
+fn test_remove_pegged_asset_clears_stored_prices() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+    let eurc = Address::generate(&e);
+
+    let (oracle_aggregator_client, _) = setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+    let pegged = PeggedAsset {
+        price: 1_0800000,
+        feed: PegFeed::Unchecked,
+        tolerance: 0,
+    };
+    oracle_aggregator_client.set_pegged_asset(&Asset::Stellar(eurc.clone()), &Some(pegged.clone()));
+    oracle_aggregator_client.set_price_bounds(
+        &Asset::Stellar(eurc.clone()),
+        &Some(PriceBounds {
+            min: 1_0000000,
+            max: 1_2000000,
+        }),
+    );
+
+    oracle_aggregator_client.set_pegged_asset(&Asset::Stellar(eurc.clone()), &None);
+    assert!(oracle_aggregator_client
+        .price_bounds(&Asset::Stellar(eurc.clone()))
+        .is_none());
+
+    // the bounds are not restored when the asset is registered again
+    oracle_aggregator_client.set_pegged_asset(&Asset::Stellar(eurc.clone()), &Some(pegged));
+    assert!(oracle_aggregator_client
+        .price_bounds(&Asset::Stellar(eurc))
+        .is_none());
+}
@@ -0,0 +1,95 @@
+#![cfg(test)]
+use crate::testutils::{setup_default_aggregator, EnvTestUtils};
+use sep_40_oracle::Asset;
+use soroban_sdk::{testutils::Address as _, vec, Address, Env, Vec};
+
+#[test]
+fn test_price() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_120000000, 1_010000000]),
+        &(recent_norm_time - 600),
+    );
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_110000000, 1_000000000]),
+        &(recent_norm_time - 300),
+    );
+
+    let price_0 = oracle_aggregator_client
+        .price(&Asset::Stellar(asset_0.clone()), &(recent_norm_time - 600))
+        .unwrap();
+    assert_eq!(price_0.price, 0_1200000);
+    assert_eq!(price_0.timestamp, recent_norm_time - 600);
+
+    // timestamps within a round return that round
+    let price_0 = oracle_aggregator_client
+        .price(&Asset::Stellar(asset_0), &(recent_norm_time - 150))
+        .unwrap();
+    assert_eq!(price_0.price, 0_1100000);
+    assert_eq!(price_0.timestamp, recent_norm_time - 300);
+
+    let price_1 = oracle_aggregator_client
+        .price(&Asset::Stellar(asset_1), &(recent_norm_time - 300))
+        .unwrap();
+    assert_eq!(price_1.price, 1_0000000);
+    assert_eq!(price_1.timestamp, recent_norm_time - 300);
+}
+
+#[test]
+fn test_price_no_round_at_timestamp() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_120000000, 1_010000000]),
+        &(recent_norm_time - 600),
+    );
+    oracle_1.set_price(&vec![&e], &(recent_norm_time - 300));
+
+    // round was missed
+    let price_0 =
+        oracle_aggregator_client.price(&Asset::Stellar(asset_0.clone()), &(recent_norm_time - 300));
+    assert!(price_0.is_none());
+
+    // round was never reported
+    let price_0 =
+        oracle_aggregator_client.price(&Asset::Stellar(asset_0), &(recent_norm_time + 300));
+    assert!(price_0.is_none());
+}
+
+#[test]
+fn test_price_checks_if_asset_is_usdc() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, _) = setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+
+    let timestamp = e.ledger().timestamp() - 12345;
+    let price = oracle_aggregator_client
+        .price(&Asset::Stellar(usdc), &timestamp)
+        .unwrap();
+    assert_eq!(price.price, 1_0000000);
+    assert_eq!(price.timestamp, timestamp);
+}
@@ -0,0 +1,299 @@
+#![cfg(test)]
+use crate::{
+    testutils::{create_asset_configs, setup_default_aggregator, EnvTestUtils},
+    types::PriceBounds,
+};
+use sep_40_oracle::Asset;
+use soroban_sdk::{
+    testutils::{Address as _, AuthorizedFunction, AuthorizedInvocation},
+    vec, Address, Env, IntoVal, Symbol, Vec,
+};
+
+#[test]
+fn test_price_bounds() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+
+    let bounds = Some(PriceBounds {
+        min: 0_0500000,
+        max: 0_2000000,
+    });
+    oracle_aggregator_client.set_price_bounds(&Asset::Stellar(asset_0.clone()), &bounds);
+    assert_eq!(
+        e.auths()[0],
+        (
+            oracle_aggregator_client.admin(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    oracle_aggregator_client.address.clone(),
+                    Symbol::new(&e, "set_price_bounds"),
+                    vec![
+                        &e,
+                        Asset::Stellar(asset_0.clone()).into_val(&e),
+                        bounds.into_val(&e)
+                    ]
+                )),
+                sub_invocations: std::vec![]
+            }
+        )
+    );
+    let stored_bounds = oracle_aggregator_client
+        .price_bounds(&Asset::Stellar(asset_0.clone()))
+        .unwrap();
+    assert_eq!(stored_bounds.min, 0_0500000);
+    assert_eq!(stored_bounds.max, 0_2000000);
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_200000000, 5_000000000]),
+        &recent_norm_time,
+    );
+
+    let price_0 = oracle_aggregator_client
+        .lastprice(&Asset::Stellar(asset_0.clone()))
+        .unwrap();
+    assert_eq!(price_0.price, 0_2000000);
+
+    // asset 1 does not have price bounds
+    let price_1 = oracle_aggregator_client
+        .lastprice(&Asset::Stellar(asset_1))
+        .unwrap();
+    assert_eq!(price_1.price, 5_0000000);
+
+    oracle_aggregator_client.set_price_bounds(&Asset::Stellar(asset_0.clone()), &None);
+    assert!(oracle_aggregator_client
+        .price_bounds(&Asset::Stellar(asset_0))
+        .is_none());
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #110)")]
+fn test_price_above_max() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+    oracle_aggregator_client.set_price_bounds(
+        &Asset::Stellar(asset_0.clone()),
+        &Some(PriceBounds {
+            min: 0_0500000,
+            max: 0_2000000,
+        }),
+    );
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_200000100, 1_000000000]),
+        &recent_norm_time,
+    );
+
+    oracle_aggregator_client.lastprice(&Asset::Stellar(asset_0));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #110)")]
+fn test_prices_below_min() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+    oracle_aggregator_client.set_price_bounds(
+        &Asset::Stellar(asset_0.clone()),
+        &Some(PriceBounds {
+            min: 0_0500000,
+            max: 0_2000000,
+        }),
+    );
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_040000000, 1_000000000]),
+        &(recent_norm_time - 300),
+    );
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_110000000, 1_000000000]),
+        &recent_norm_time,
+    );
+
+    oracle_aggregator_client.prices(&Asset::Stellar(asset_0), &2);
+}
+
+#[test]
+fn test_zero_price_dropped() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    oracle_1.set_price(&Vec::from_array(&e, [0, 1_000000000]), &recent_norm_time);
+
+    assert!(oracle_aggregator_client
+        .lastprice(&Asset::Stellar(asset_0))
+        .is_none());
+}
+
+#[test]
+fn test_negative_price_dropped() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    oracle_1.set_price(
+        &Vec::from_array(&e, [1_000000000, -1_000000000]),
+        &recent_norm_time,
+    );
+
+    let prices = oracle_aggregator_client.lastprices(&vec![
+        &e,
+        Asset::Stellar(asset_0),
+        Asset::Stellar(asset_1.clone()),
+    ]);
+    assert_eq!(prices.get_unchecked(0).unwrap().price, 1_0000000);
+    assert!(prices.get_unchecked(1).is_none());
+    assert!(oracle_aggregator_client
+        .price(&Asset::Stellar(asset_1), &recent_norm_time)
+        .is_none());
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #109)")]
+fn test_set_price_bounds_min_not_positive() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, _) = setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+
+    oracle_aggregator_client.set_price_bounds(
+        &Asset::Stellar(asset_0),
+        &Some(PriceBounds {
+            min: 0,
+            max: 0_2000000,
+        }),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #109)")]
+fn test_set_price_bounds_min_over_max() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, _) = setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+
+    oracle_aggregator_client.set_price_bounds(
+        &Asset::Stellar(asset_0),
+        &Some(PriceBounds {
+            min: 0_2000001,
+            max: 0_2000000,
+        }),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Error(Auth, InvalidAction)")]
+fn test_set_price_bounds_requires_admin() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, _) = setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+
+    e.set_auths(&[]);
+    oracle_aggregator_client.set_price_bounds(&Asset::Stellar(asset_0), &None);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #101)")]
+fn test_set_price_bounds_unlisted_asset() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, _) = setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+
+    oracle_aggregator_client.set_price_bounds(
+        &Asset::Stellar(Address::generate(&e)),
+        &Some(PriceBounds {
+            min: 0_1000000,
+            max: 0_2000000,
+        }),
+    );
+}
+
+#[test]
+fn test_set_decimals_rescales_usdc_bounds_once() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+    // USDC with an asset config is listed both as a configured asset and as USDC
+    let oracles = vec![&e, oracle.address.clone()];
+    let usdc_config = create_asset_configs(&e, &oracles, &vec![&e, usdc.clone()])
+        .get_unchecked(0)
+        .1;
+    oracle_aggregator_client.set_asset_config(&Asset::Stellar(usdc.clone()), &usdc_config);
+    oracle_aggregator_client.set_price_bounds(
+        &Asset::Stellar(usdc.clone()),
+        &Some(PriceBounds {
+            min: 0_9000000,
+            max: 1_1000000,
+        }),
+    );
+
+    oracle_aggregator_client.set_decimals(&8);
+
+    let bounds = oracle_aggregator_client
+        .price_bounds(&Asset::Stellar(usdc))
+        .unwrap();
+    assert_eq!(bounds.min, 0_90000000);
+    assert_eq!(bounds.max, 1_10000000);
+}
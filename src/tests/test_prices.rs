@@ -0,0 +1,187 @@
+#![cfg(test)]
+use crate::{
+    price_data::MAX_RECORDS,
+    testutils::{
+        create_asset_configs, create_mock_oracle, create_oracle_aggregator,
+        setup_default_aggregator, EnvTestUtils,
+    },
+};
+use sep_40_oracle::Asset;
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    vec, Address, Env, Vec,
+};
+
+#[test]
+fn test_prices() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_130000000, 1_020000000]),
+        &(recent_norm_time - 600),
+    );
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_120000000, 1_010000000]),
+        &(recent_norm_time - 300),
+    );
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_110000000, 1_000000000]),
+        &recent_norm_time,
+    );
+
+    let prices_0 = oracle_aggregator_client
+        .prices(&Asset::Stellar(asset_0.clone()), &2)
+        .unwrap();
+    assert_eq!(prices_0.len(), 2);
+    assert_eq!(prices_0.get_unchecked(0).price, 0_1100000);
+    assert_eq!(prices_0.get_unchecked(0).timestamp, recent_norm_time);
+    assert_eq!(prices_0.get_unchecked(1).price, 0_1200000);
+    assert_eq!(prices_0.get_unchecked(1).timestamp, recent_norm_time - 300);
+
+    let prices_1 = oracle_aggregator_client
+        .prices(&Asset::Stellar(asset_1), &3)
+        .unwrap();
+    assert_eq!(prices_1.len(), 3);
+    assert_eq!(prices_1.get_unchecked(0).price, 1_0000000);
+    assert_eq!(prices_1.get_unchecked(1).price, 1_0100000);
+    assert_eq!(prices_1.get_unchecked(2).price, 1_0200000);
+    assert_eq!(prices_1.get_unchecked(2).timestamp, recent_norm_time - 600);
+}
+
+#[test]
+fn test_prices_skips_missing_rounds() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_130000000, 1_020000000]),
+        &(recent_norm_time - 600),
+    );
+    oracle_1.set_price(&vec![&e], &(recent_norm_time - 300));
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_110000000, 1_000000000]),
+        &recent_norm_time,
+    );
+
+    let prices_0 = oracle_aggregator_client
+        .prices(&Asset::Stellar(asset_0), &3)
+        .unwrap();
+    assert_eq!(prices_0.len(), 2);
+    assert_eq!(prices_0.get_unchecked(0).price, 0_1100000);
+    assert_eq!(prices_0.get_unchecked(0).timestamp, recent_norm_time);
+    assert_eq!(prices_0.get_unchecked(1).price, 0_1300000);
+    assert_eq!(prices_0.get_unchecked(1).timestamp, recent_norm_time - 600);
+}
+
+#[test]
+fn test_prices_checks_if_asset_is_usdc() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, _) = setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    let prices = oracle_aggregator_client
+        .prices(&Asset::Stellar(usdc), &3)
+        .unwrap();
+    assert_eq!(prices.len(), 3);
+    for (i, price) in prices.iter().enumerate() {
+        assert_eq!(price.price, 1_0000000);
+        assert_eq!(price.timestamp, recent_norm_time - 300 * i as u64);
+    }
+}
+
+#[test]
+fn test_prices_usdc_records_capped() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, _) = setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+
+    let prices = oracle_aggregator_client
+        .prices(&Asset::Stellar(usdc.clone()), &u32::MAX)
+        .unwrap();
+    assert_eq!(prices.len(), MAX_RECORDS);
+
+    // the rounds stop at the first round
+    e.ledger().with_mut(|li| li.timestamp = 600);
+    let prices = oracle_aggregator_client
+        .prices(&Asset::Stellar(usdc), &5)
+        .unwrap();
+    assert_eq!(prices.len(), 3);
+    assert_eq!(prices.get_unchecked(2).timestamp, 0);
+}
+
+#[test]
+fn test_prices_reads_each_source_once() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let oracle_0 = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let oracle_1 = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let oracle_2 = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let oracles = vec![
+        &e,
+        oracle_0.address.clone(),
+        oracle_1.address.clone(),
+        oracle_2.address.clone(),
+    ];
+    let (_, oracle_aggregator_client) = create_oracle_aggregator(
+        &e,
+        &Address::generate(&e),
+        &oracles,
+        &usdc,
+        &7,
+        &900,
+        &create_asset_configs(&e, &oracles, &vec![&e, asset_0.clone(), asset_1.clone()]),
+        &None,
+    );
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    for i in (0..MAX_RECORDS as u64 + 5).rev() {
+        let timestamp = recent_norm_time - 300 * i;
+        oracle_0.set_price(&Vec::from_array(&e, [0_110000000, 1_000000000]), &timestamp);
+        oracle_1.set_price(&Vec::from_array(&e, [0_120000000, 1_000000000]), &timestamp);
+        oracle_2.set_price(&Vec::from_array(&e, [0_130000000, 1_000000000]), &timestamp);
+    }
+
+    // the records are capped, and each round is the median of the source oracles
+    let prices = oracle_aggregator_client
+        .prices(&Asset::Stellar(asset_0), &(MAX_RECORDS + 5))
+        .unwrap();
+    assert!(e.cost_estimate().resources().instructions < 100_000_000);
+    assert_eq!(prices.len(), MAX_RECORDS);
+    for (i, price) in prices.iter().enumerate() {
+        assert_eq!(price.price, 0_1200000);
+        assert_eq!(price.timestamp, recent_norm_time - 300 * i as u64);
+    }
+}
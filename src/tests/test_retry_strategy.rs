@@ -0,0 +1,317 @@
+#![cfg(test)]
+use crate::{
+    testutils::{
+        create_asset_configs, create_oracle_aggregator, setup_default_aggregator, EnvTestUtils,
+    },
+    types::{PriceMethod, RetryStrategy},
+};
+use sep_40_oracle::{Asset, PriceData, PriceFeedTrait};
+use soroban_sdk::{
+    contract, contractimpl, symbol_short,
+    testutils::{Address as _, AuthorizedFunction, AuthorizedInvocation, Events},
+    vec, Address, Env, IntoVal, Symbol, Vec,
+};
+
+/// A source oracle that skips missing rounds in `prices`, like a Reflector oracle
+#[contract]
+struct HistoryOracle;
+
+#[contractimpl]
+impl HistoryOracle {
+    pub fn __constructor(e: Env, base: Asset, assets: Vec<Asset>) {
+        e.storage().instance().set(&symbol_short!("base"), &base);
+        e.storage()
+            .instance()
+            .set(&symbol_short!("assets"), &assets);
+    }
+
+    /// Set the price of an asset for the round starting at `timestamp`, and make it the most
+    /// recent round
+    pub fn set_price(e: Env, asset: Asset, price: i128, timestamp: u64) {
+        e.storage().persistent().set(&(asset, timestamp), &price);
+        e.storage()
+            .instance()
+            .set(&symbol_short!("last"), &timestamp);
+    }
+
+    /// Make the round starting at `timestamp` the most recent round
+    pub fn set_last(e: Env, timestamp: u64) {
+        e.storage()
+            .instance()
+            .set(&symbol_short!("last"), &timestamp);
+    }
+}
+
+#[contractimpl]
+impl PriceFeedTrait for HistoryOracle {
+    fn base(e: Env) -> Asset {
+        e.storage().instance().get(&symbol_short!("base")).unwrap()
+    }
+
+    fn assets(e: Env) -> Vec<Asset> {
+        e.storage()
+            .instance()
+            .get(&symbol_short!("assets"))
+            .unwrap()
+    }
+
+    fn decimals(_e: Env) -> u32 {
+        9
+    }
+
+    fn resolution(_e: Env) -> u32 {
+        300
+    }
+
+    fn price(e: Env, asset: Asset, timestamp: u64) -> Option<PriceData> {
+        let timestamp = timestamp / 300 * 300;
+        let price: i128 = e.storage().persistent().get(&(asset, timestamp))?;
+        Some(PriceData { price, timestamp })
+    }
+
+    fn prices(e: Env, asset: Asset, records: u32) -> Option<Vec<PriceData>> {
+        let mut timestamp: u64 = e.storage().instance().get(&symbol_short!("last"))?;
+        let mut prices = Vec::new(&e);
+        for _ in 0..records {
+            if let Some(price) = Self::price(e.clone(), asset.clone(), timestamp) {
+                prices.push_back(price);
+            }
+            timestamp -= 300;
+        }
+        if prices.is_empty() {
+            return None;
+        }
+        Some(prices)
+    }
+
+    fn lastprice(e: Env, asset: Asset) -> Option<PriceData> {
+        let timestamp: u64 = e.storage().instance().get(&symbol_short!("last"))?;
+        Self::price(e, asset, timestamp)
+    }
+}
+
+#[test]
+fn test_set_retry_strategy() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, _) = setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+    assert_eq!(
+        oracle_aggregator_client.retry_strategy(),
+        RetryStrategy::Price
+    );
+
+    oracle_aggregator_client.set_retry_strategy(&RetryStrategy::Prices);
+    assert_eq!(
+        e.auths()[0],
+        (
+            oracle_aggregator_client.admin(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    oracle_aggregator_client.address.clone(),
+                    Symbol::new(&e, "set_retry_strategy"),
+                    vec![&e, RetryStrategy::Prices.into_val(&e)]
+                )),
+                sub_invocations: std::vec![]
+            }
+        )
+    );
+    assert_eq!(
+        oracle_aggregator_client.retry_strategy(),
+        RetryStrategy::Prices
+    );
+}
+
+#[test]
+fn test_prices_retry_strategy() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let assets = vec![
+        &e,
+        Asset::Stellar(asset_0.clone()),
+        Asset::Stellar(asset_1.clone()),
+    ];
+    let oracle_id = e.register(HistoryOracle, (Asset::Stellar(usdc.clone()), assets));
+    let oracle = HistoryOracleClient::new(&e, &oracle_id);
+    let oracles = vec![&e, oracle_id.clone()];
+    let (_, oracle_aggregator_client) = create_oracle_aggregator(
+        &e,
+        &Address::generate(&e),
+        &oracles,
+        &usdc,
+        &7,
+        &900,
+        &create_asset_configs(&e, &oracles, &vec![&e, asset_0.clone(), asset_1.clone()]),
+        &None,
+    );
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    let asset = Asset::Stellar(asset_0.clone());
+    oracle.set_price(&asset, &0_100000000, &(recent_norm_time - 900));
+    oracle.set_price(&asset, &0_110000000, &(recent_norm_time - 600));
+    // the two most recent rounds are missing
+    oracle.set_last(&recent_norm_time);
+
+    let detail = oracle_aggregator_client.lastprice_detail(&asset).unwrap();
+    assert_eq!(detail.price.price, 0_1100000);
+    assert_eq!(detail.price.timestamp, recent_norm_time - 600);
+    assert_eq!(detail.method, PriceMethod::Price);
+    assert_eq!(detail.steps, 2);
+
+    // the newest round within `max_age` is used, and takes the same number of steps back
+    oracle_aggregator_client.set_retry_strategy(&RetryStrategy::Prices);
+    let detail = oracle_aggregator_client.lastprice_detail(&asset).unwrap();
+    assert_eq!(detail.price.price, 0_1100000);
+    assert_eq!(detail.price.timestamp, recent_norm_time - 600);
+    assert_eq!(detail.method, PriceMethod::Prices);
+    assert_eq!(detail.steps, 2);
+    let event = e.events().all().get_unchecked(0);
+    assert_eq!(
+        vec![&e, event],
+        vec![
+            &e,
+            (
+                oracle_aggregator_client.address.clone(),
+                (Symbol::new(&e, "price_retry"), asset.clone()).into_val(&e),
+                (oracle_id.clone(), 2u32, recent_norm_time - 600).into_val(&e)
+            )
+        ]
+    );
+
+    // rounds older than `max_age` are not used
+    e.jump(120);
+    oracle.set_last(&(recent_norm_time + 600));
+    let price = oracle_aggregator_client.lastprice(&asset);
+    assert!(price.is_none());
+}
+
+#[test]
+fn test_prices_retry_strategy_reads_fewer_entries() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    oracle_1.set_price(&Vec::from_array(&e, []), &(recent_norm_time - 1200));
+    oracle_1.set_price(&Vec::from_array(&e, []), &(recent_norm_time - 900));
+    oracle_1.set_price(&Vec::from_array(&e, []), &(recent_norm_time - 600));
+    oracle_1.set_price(&Vec::from_array(&e, []), &(recent_norm_time - 300));
+    oracle_1.set_price(&Vec::from_array(&e, []), &recent_norm_time);
+
+    // jump 1 block to ensure the most recent price is > 900 seconds old
+    e.jump(1);
+
+    let price_0 = oracle_aggregator_client.lastprice(&Asset::Stellar(asset_0.clone()));
+    let price_read_entries = e.cost_estimate().resources().read_entries;
+    assert!(price_0.is_none());
+
+    oracle_aggregator_client.set_retry_strategy(&RetryStrategy::Prices);
+    let price_0 = oracle_aggregator_client.lastprice(&Asset::Stellar(asset_0));
+    let prices_read_entries = e.cost_estimate().resources().read_entries;
+    assert!(price_0.is_none());
+    // the 3 rounds queried by `price` are not read, as `prices` stops at the missing most recent
+    // round already read by `lastprice`
+    assert!(prices_read_entries < price_read_entries);
+    assert_eq!(price_read_entries - prices_read_entries, 3);
+}
+
+#[test]
+fn test_prices_retry_strategy_reads_found_round() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let assets = vec![
+        &e,
+        Asset::Stellar(asset_0.clone()),
+        Asset::Stellar(asset_1.clone()),
+    ];
+    let oracle_id = e.register(HistoryOracle, (Asset::Stellar(usdc.clone()), assets));
+    let oracle = HistoryOracleClient::new(&e, &oracle_id);
+    let oracles = vec![&e, oracle_id.clone()];
+    let (_, oracle_aggregator_client) = create_oracle_aggregator(
+        &e,
+        &Address::generate(&e),
+        &oracles,
+        &usdc,
+        &7,
+        &900,
+        &create_asset_configs(&e, &oracles, &vec![&e, asset_0.clone(), asset_1.clone()]),
+        &None,
+    );
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    let asset = Asset::Stellar(asset_0.clone());
+    oracle.set_price(&asset, &0_110000000, &(recent_norm_time - 600));
+    oracle.set_last(&recent_norm_time);
+    // cache the price first, so both strategies read the same cache entry
+    oracle_aggregator_client.lastprice(&asset).unwrap();
+
+    let price = oracle_aggregator_client.lastprice(&asset).unwrap();
+    let price_resources = e.cost_estimate().resources();
+    oracle_aggregator_client.set_retry_strategy(&RetryStrategy::Prices);
+    let prices_price = oracle_aggregator_client.lastprice(&asset).unwrap();
+    let prices_resources = e.cost_estimate().resources();
+    assert_eq!(price.timestamp, recent_norm_time - 600);
+    assert_eq!(prices_price.timestamp, price.timestamp);
+    assert_eq!(prices_price.price, price.price);
+    // `prices` also reads the round older than the one found, as it fetches every round within
+    // `max_age` at once, but replaces the `price` calls with a single call
+    assert_eq!(
+        prices_resources.read_entries,
+        price_resources.read_entries + 1
+    );
+    assert!(prices_resources.instructions < price_resources.instructions);
+
+    // if the round found is the oldest within `max_age`, both strategies read the same rounds
+    oracle.set_price(&asset, &0_100000000, &(recent_norm_time - 900));
+    oracle.set_last(&recent_norm_time);
+    e.as_contract(&oracle_id, || {
+        e.storage()
+            .persistent()
+            .remove(&(asset.clone(), recent_norm_time - 600));
+    });
+    let prices_price = oracle_aggregator_client.lastprice(&asset).unwrap();
+    let prices_resources = e.cost_estimate().resources();
+    oracle_aggregator_client.set_retry_strategy(&RetryStrategy::Price);
+    let price = oracle_aggregator_client.lastprice(&asset).unwrap();
+    let price_resources = e.cost_estimate().resources();
+    assert_eq!(price.timestamp, recent_norm_time - 900);
+    assert_eq!(prices_price.timestamp, price.timestamp);
+    assert_eq!(prices_resources.read_entries, price_resources.read_entries);
+    assert!(prices_resources.instructions < price_resources.instructions);
+}
+
+#[test]
+#[should_panic(expected = "Error(Auth, InvalidAction)")]
+fn test_set_retry_strategy_requires_admin() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, _) = setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+
+    e.set_auths(&[]);
+    oracle_aggregator_client.set_retry_strategy(&RetryStrategy::Prices);
+}
@@ -0,0 +1,180 @@
+#![cfg(test)]
+use crate::{
+    testutils::{
+        create_asset_configs, create_mock_oracle, create_oracle_aggregator,
+        setup_default_aggregator, EnvTestUtils,
+    },
+    types::RoundingMode,
+};
+use sep_40_oracle::Asset;
+use soroban_sdk::{
+    testutils::{Address as _, AuthorizedFunction, AuthorizedInvocation},
+    vec, Address, Env, IntoVal, Symbol, Vec,
+};
+
+#[test]
+fn test_rounding() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+    assert_eq!(oracle_aggregator_client.rounding(), RoundingMode::Floor);
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_123456789, 1_000000050]),
+        &recent_norm_time,
+    );
+
+    let price_0 = oracle_aggregator_client
+        .lastprice(&Asset::Stellar(asset_0.clone()))
+        .unwrap();
+    assert_eq!(price_0.price, 0_1234567);
+
+    oracle_aggregator_client.set_rounding(&RoundingMode::Ceil);
+    assert_eq!(
+        e.auths()[0],
+        (
+            oracle_aggregator_client.admin(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    oracle_aggregator_client.address.clone(),
+                    Symbol::new(&e, "set_rounding"),
+                    vec![&e, RoundingMode::Ceil.into_val(&e)]
+                )),
+                sub_invocations: std::vec![]
+            }
+        )
+    );
+    assert_eq!(oracle_aggregator_client.rounding(), RoundingMode::Ceil);
+    let price_0 = oracle_aggregator_client
+        .lastprice(&Asset::Stellar(asset_0.clone()))
+        .unwrap();
+    assert_eq!(price_0.price, 0_1234568);
+    let price_1 = oracle_aggregator_client
+        .lastprice(&Asset::Stellar(asset_1.clone()))
+        .unwrap();
+    assert_eq!(price_1.price, 1_0000001);
+
+    // ties round to the even value
+    oracle_aggregator_client.set_rounding(&RoundingMode::HalfEven);
+    let price_0 = oracle_aggregator_client
+        .lastprice(&Asset::Stellar(asset_0))
+        .unwrap();
+    assert_eq!(price_0.price, 0_1234568);
+    let price_1 = oracle_aggregator_client
+        .lastprice(&Asset::Stellar(asset_1))
+        .unwrap();
+    assert_eq!(price_1.price, 1_0000000);
+}
+
+#[test]
+fn test_lastprice_rounded() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_123456789, 1_000000050]),
+        &recent_norm_time,
+    );
+
+    // collateral rounds down and liabilities round up, with the same configured rounding
+    let collateral_price = oracle_aggregator_client
+        .lastprice_rounded(&Asset::Stellar(asset_0.clone()), &RoundingMode::Floor)
+        .unwrap();
+    assert_eq!(collateral_price.price, 0_1234567);
+    let liability_price = oracle_aggregator_client
+        .lastprice_rounded(&Asset::Stellar(asset_0.clone()), &RoundingMode::Ceil)
+        .unwrap();
+    assert_eq!(liability_price.price, 0_1234568);
+    assert_eq!(liability_price.timestamp, recent_norm_time);
+    assert_eq!(oracle_aggregator_client.rounding(), RoundingMode::Floor);
+    let price_0 = oracle_aggregator_client
+        .lastprice(&Asset::Stellar(asset_0))
+        .unwrap();
+    assert_eq!(price_0.price, 0_1234567);
+
+    let usdc_price = oracle_aggregator_client
+        .lastprice_rounded(&Asset::Stellar(usdc), &RoundingMode::Ceil)
+        .unwrap();
+    assert_eq!(usdc_price.price, 1_0000000);
+}
+
+#[test]
+fn test_normalize_price_overflow_drops_source() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let oracle_0 = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let oracle_1 = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let oracles = vec![&e, oracle_0.address.clone(), oracle_1.address.clone()];
+    let assets = create_asset_configs(&e, &oracles, &vec![&e, asset_0.clone()]);
+    let (_, oracle_aggregator_client) = create_oracle_aggregator(
+        &e,
+        &Address::generate(&e),
+        &oracles,
+        &usdc,
+        &38,
+        &900,
+        &assets,
+        &None,
+    );
+
+    // 5 * 10^38 does not fit in an i128, so oracle_0 is dropped like a stale source
+    oracle_0.set_price(
+        &Vec::from_array(&e, [5_000000000, 1_000000000]),
+        &e.ledger().timestamp(),
+    );
+    oracle_1.set_price(
+        &Vec::from_array(&e, [1_000000000, 1_000000000]),
+        &e.ledger().timestamp(),
+    );
+
+    let sourced_price = oracle_aggregator_client
+        .lastprice_with_source(&Asset::Stellar(asset_0.clone()))
+        .unwrap();
+    assert_eq!(sourced_price.price.price, 10_i128.pow(38));
+    assert_eq!(sourced_price.sources, vec![&e, oracle_1.address.clone()]);
+
+    // without a source that can be normalized, there is no price
+    oracle_1.set_price(
+        &Vec::from_array(&e, [5_000000000, 1_000000000]),
+        &e.ledger().timestamp(),
+    );
+    assert!(oracle_aggregator_client
+        .lastprice(&Asset::Stellar(asset_0))
+        .is_none());
+}
+
+#[test]
+#[should_panic(expected = "Error(Auth, InvalidAction)")]
+fn test_set_rounding_requires_admin() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, _) = setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+
+    e.set_auths(&[]);
+    oracle_aggregator_client.set_rounding(&RoundingMode::Ceil);
+}
@@ -0,0 +1,172 @@
+#![cfg(test)]
+use crate::testutils::{
+    create_asset_configs, create_mock_oracle, create_oracle_aggregator, setup_default_aggregator,
+    EnvTestUtils,
+};
+use sep_40_oracle::Asset;
+use soroban_sdk::{testutils::Address as _, vec, Address, Env, Vec};
+
+#[test]
+fn test_twap() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_130000000, 1_020000000]),
+        &(recent_norm_time - 600),
+    );
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_120000000, 1_010000000]),
+        &(recent_norm_time - 300),
+    );
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_110000000, 5_000000000]),
+        &recent_norm_time,
+    );
+
+    let twap_0 = oracle_aggregator_client.twap(&Asset::Stellar(asset_0), &3);
+    assert_eq!(twap_0.price, 0_1200000);
+    assert_eq!(twap_0.timestamp, recent_norm_time);
+
+    // a single round is not able to move the average as much as the last price
+    let twap_1 = oracle_aggregator_client.twap(&Asset::Stellar(asset_1.clone()), &3);
+    assert_eq!(twap_1.price, 2_3433333);
+
+    let twap_1 = oracle_aggregator_client.twap(&Asset::Stellar(asset_1), &1);
+    assert_eq!(twap_1.price, 5_0000000);
+}
+
+#[test]
+fn test_twap_median_of_sources() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let oracle_0 = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let oracle_1 = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let oracle_2 = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let oracles = vec![
+        &e,
+        oracle_0.address.clone(),
+        oracle_1.address.clone(),
+        oracle_2.address.clone(),
+    ];
+    let (_, oracle_aggregator_client) = create_oracle_aggregator(
+        &e,
+        &Address::generate(&e),
+        &oracles,
+        &usdc,
+        &7,
+        &900,
+        &create_asset_configs(&e, &oracles, &vec![&e, asset_0.clone(), asset_1.clone()]),
+        &None,
+    );
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    for timestamp in [recent_norm_time - 300, recent_norm_time] {
+        // the first source oracle is manipulated
+        oracle_0.set_price(&Vec::from_array(&e, [9_000000000, 1_000000000]), &timestamp);
+        oracle_1.set_price(&Vec::from_array(&e, [0_110000000, 1_000000000]), &timestamp);
+        oracle_2.set_price(&Vec::from_array(&e, [0_130000000, 1_000000000]), &timestamp);
+    }
+
+    // each round is the median of the source oracles
+    let prices = oracle_aggregator_client
+        .prices(&Asset::Stellar(asset_0.clone()), &2)
+        .unwrap();
+    assert_eq!(prices.len(), 2);
+    assert_eq!(prices.get_unchecked(0).price, 0_1300000);
+    assert_eq!(prices.get_unchecked(1).price, 0_1300000);
+    assert_eq!(prices.get_unchecked(1).timestamp, recent_norm_time - 300);
+
+    let twap = oracle_aggregator_client.twap(&Asset::Stellar(asset_0), &2);
+    assert_eq!(twap.price, 0_1300000);
+    assert_eq!(twap.timestamp, recent_norm_time);
+}
+
+#[test]
+fn test_twap_usdc() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, _) = setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+
+    let twap = oracle_aggregator_client.twap(&Asset::Stellar(usdc), &3);
+    assert_eq!(twap.price, 1_0000000);
+    assert_eq!(twap.timestamp, e.ledger().timestamp() / 300 * 300);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #107)")]
+fn test_twap_missing_rounds() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_130000000, 1_020000000]),
+        &(recent_norm_time - 600),
+    );
+    oracle_1.set_price(&Vec::from_array(&e, []), &(recent_norm_time - 300));
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_110000000, 1_000000000]),
+        &recent_norm_time,
+    );
+
+    oracle_aggregator_client.twap(&Asset::Stellar(asset_0), &3);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #107)")]
+fn test_twap_rounds_over_max_age() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_130000000, 1_020000000]),
+        &(recent_norm_time - 900),
+    );
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_120000000, 1_010000000]),
+        &(recent_norm_time - 600),
+    );
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_110000000, 1_000000000]),
+        &(recent_norm_time - 300),
+    );
+
+    // jump 1 block to ensure the oldest round is > 900 seconds old
+    e.jump(1);
+
+    oracle_aggregator_client.twap(&Asset::Stellar(asset_0), &3);
+}
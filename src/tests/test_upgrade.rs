@@ -0,0 +1,236 @@
+#![cfg(test)]
+use crate::{
+    errors::OracleAggregatorErrors,
+    storage,
+    testutils::{
+        create_asset_configs, create_mock_oracle, create_oracle_aggregator, oracle_aggregator,
+        EnvTestUtils,
+    },
+};
+use sep_40_oracle::Asset;
+use soroban_sdk::{
+    testutils::{Address as _, AuthorizedFunction, AuthorizedInvocation},
+    vec, Address, Env, IntoVal, String, Symbol,
+};
+
+#[test]
+fn test_version() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let oracle = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let (_, oracle_aggregator_client) = create_oracle_aggregator(
+        &e,
+        &Address::generate(&e),
+        &vec![&e, oracle.address.clone()],
+        &usdc,
+        &7,
+        &900,
+        &vec![&e],
+        &None,
+    );
+
+    let version = oracle_aggregator_client.version();
+    assert_eq!(
+        version.contract,
+        String::from_str(&e, env!("CARGO_PKG_VERSION"))
+    );
+    assert_eq!(version.schema, 1);
+}
+
+#[test]
+fn test_upgrade() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let oracle = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let (aggregator_id, oracle_aggregator_client) = create_oracle_aggregator(
+        &e,
+        &admin,
+        &vec![&e, oracle.address.clone()],
+        &usdc,
+        &7,
+        &900,
+        &vec![&e],
+        &None,
+    );
+
+    let wasm_hash = e.deployer().upload_contract_wasm(oracle_aggregator::WASM);
+    oracle_aggregator_client.upgrade(&wasm_hash);
+    assert_eq!(
+        e.auths()[0],
+        (
+            admin.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    aggregator_id.clone(),
+                    Symbol::new(&e, "upgrade"),
+                    vec![&e, wasm_hash.into_val(&e)]
+                )),
+                sub_invocations: std::vec![]
+            }
+        )
+    );
+
+    // storage is kept across the upgrade
+    assert_eq!(oracle_aggregator_client.admin(), admin);
+    assert_eq!(oracle_aggregator_client.max_age(), 900);
+}
+
+#[test]
+#[should_panic(expected = "Error(Auth, InvalidAction)")]
+fn test_upgrade_requires_admin() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let oracle = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let (_, oracle_aggregator_client) = create_oracle_aggregator(
+        &e,
+        &Address::generate(&e),
+        &vec![&e, oracle.address.clone()],
+        &usdc,
+        &7,
+        &900,
+        &vec![&e],
+        &None,
+    );
+
+    let wasm_hash = e.deployer().upload_contract_wasm(oracle_aggregator::WASM);
+    e.set_auths(&[]);
+    oracle_aggregator_client.upgrade(&wasm_hash);
+}
+
+#[test]
+fn test_upgrade_and_migrate() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let admin = Address::generate(&e);
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let oracle = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let oracles = vec![&e, oracle.address.clone()];
+    let assets = create_asset_configs(&e, &oracles, &vec![&e, asset_0.clone()]);
+    let (aggregator_id, oracle_aggregator_client) =
+        create_oracle_aggregator(&e, &admin, &oracles, &usdc, &7, &900, &assets, &None);
+    oracle.set_price(&vec![&e, 0_110000000, 1_000000000], &e.ledger().timestamp());
+    let price = oracle_aggregator_client
+        .lastprice(&Asset::Stellar(asset_0.clone()))
+        .unwrap();
+
+    let wasm_hash = e.deployer().upload_contract_wasm(oracle_aggregator::WASM);
+    oracle_aggregator_client.upgrade(&wasm_hash);
+    oracle_aggregator_client.migrate();
+    assert_eq!(
+        e.auths()[0],
+        (
+            admin.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    aggregator_id.clone(),
+                    Symbol::new(&e, "migrate"),
+                    vec![&e]
+                )),
+                sub_invocations: std::vec![]
+            }
+        )
+    );
+
+    assert_eq!(oracle_aggregator_client.version().schema, 1);
+    let configs = oracle_aggregator_client.config();
+    assert_eq!(configs.len(), 1);
+    assert_eq!(configs.get_unchecked(0).oracle_id, oracle.address);
+    let migrated_price = oracle_aggregator_client
+        .lastprice(&Asset::Stellar(asset_0))
+        .unwrap();
+    assert_eq!(migrated_price.price, price.price);
+    assert_eq!(migrated_price.timestamp, price.timestamp);
+}
+
+#[test]
+fn test_prices_require_migrated_schema() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let oracle = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let oracles = vec![&e, oracle.address.clone()];
+    let assets = create_asset_configs(&e, &oracles, &vec![&e, asset_0.clone()]);
+    let (aggregator_id, oracle_aggregator_client) = create_oracle_aggregator(
+        &e,
+        &Address::generate(&e),
+        &oracles,
+        &usdc,
+        &7,
+        &900,
+        &assets,
+        &None,
+    );
+    oracle.set_price(&vec![&e, 0_110000000, 1_000000000], &e.ledger().timestamp());
+    let asset = Asset::Stellar(asset_0);
+
+    // the storage of an upgraded contract is on an older schema until it is migrated
+    e.as_contract(&aggregator_id, || {
+        storage::set_schema_version(&e, &0);
+    });
+    let result = oracle_aggregator_client.try_lastprice(&asset);
+    assert_eq!(
+        result.err(),
+        Some(Ok(OracleAggregatorErrors::InvalidSchemaVersion.into()))
+    );
+    let result = oracle_aggregator_client.try_twap(&asset, &1);
+    assert_eq!(
+        result.err(),
+        Some(Ok(OracleAggregatorErrors::InvalidSchemaVersion.into()))
+    );
+
+    oracle_aggregator_client.migrate();
+    assert_eq!(oracle_aggregator_client.version().schema, 1);
+    assert!(oracle_aggregator_client.lastprice(&asset).is_some());
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #105)")]
+fn test_migrate_newer_schema() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let oracle = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let (aggregator_id, oracle_aggregator_client) = create_oracle_aggregator(
+        &e,
+        &Address::generate(&e),
+        &vec![&e, oracle.address.clone()],
+        &usdc,
+        &7,
+        &900,
+        &vec![&e],
+        &None,
+    );
+
+    e.as_contract(&aggregator_id, || {
+        storage::set_schema_version(&e, &2);
+    });
+    oracle_aggregator_client.migrate();
+}
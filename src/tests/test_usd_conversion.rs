@@ -0,0 +1,228 @@
+#![cfg(test)]
+use crate::{
+    testutils::{create_usd_oracle, setup_default_aggregator, EnvTestUtils},
+    types::{AssetConfig, AssetSource, UsdConversion, UsdConversionConfig},
+};
+use sep_40_oracle::Asset;
+use soroban_sdk::{
+    testutils::{Address as _, AuthorizedFunction, AuthorizedInvocation},
+    vec, Address, Env, IntoVal, Symbol, Vec,
+};
+
+/// Build an asset config that prices an asset from a single oracle
+fn single_source_config(e: &Env, oracle_id: &Address, asset: &Address) -> AssetConfig {
+    AssetConfig {
+        sources: vec![
+            e,
+            AssetSource {
+                oracle_id: oracle_id.clone(),
+                asset: Asset::Stellar(asset.clone()),
+            },
+        ],
+        fallbacks: Vec::new(e),
+        max_age: None,
+    }
+}
+
+#[test]
+fn test_usd_conversion_fixed() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+    let cex_oracle = create_usd_oracle(&e, &asset_1);
+
+    let conversion = Some(UsdConversion::Fixed);
+    oracle_aggregator_client.set_usd_conversion(&conversion);
+    assert_eq!(
+        e.auths()[0],
+        (
+            oracle_aggregator_client.admin(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    oracle_aggregator_client.address.clone(),
+                    Symbol::new(&e, "set_usd_conversion"),
+                    vec![&e, conversion.into_val(&e)]
+                )),
+                sub_invocations: std::vec![]
+            }
+        )
+    );
+    assert!(matches!(
+        oracle_aggregator_client.usd_conversion(),
+        Some(UsdConversionConfig::Fixed)
+    ));
+
+    // sources that report prices in USD are accepted once a USD conversion is set
+    oracle_aggregator_client.set_oracles(&vec![
+        &e,
+        oracle_1.address.clone(),
+        cex_oracle.address.clone(),
+    ]);
+    oracle_aggregator_client.set_asset_config(
+        &Asset::Stellar(asset_1.clone()),
+        &single_source_config(&e, &cex_oracle.address, &asset_1),
+    );
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    cex_oracle.set_price(&Vec::from_array(&e, [1_98000000000000]), &recent_norm_time);
+
+    let price = oracle_aggregator_client
+        .lastprice(&Asset::Stellar(asset_1))
+        .unwrap();
+    assert_eq!(price.price, 1_9800000);
+    assert_eq!(price.timestamp, recent_norm_time);
+}
+
+#[test]
+fn test_usd_conversion_source() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+    let cex_oracle = create_usd_oracle(&e, &asset_1);
+    let usd_oracle = create_usd_oracle(&e, &usdc);
+
+    oracle_aggregator_client.set_usd_conversion(&Some(UsdConversion::Source(AssetSource {
+        oracle_id: usd_oracle.address.clone(),
+        asset: Asset::Stellar(usdc.clone()),
+    })));
+    match oracle_aggregator_client.usd_conversion() {
+        Some(UsdConversionConfig::Source(source)) => {
+            assert_eq!(source.oracle.oracle_id, usd_oracle.address);
+            assert_eq!(source.oracle.decimals, 14);
+        }
+        _ => panic!("expected a USD conversion source"),
+    }
+    oracle_aggregator_client.set_oracles(&vec![
+        &e,
+        oracle_1.address.clone(),
+        cex_oracle.address.clone(),
+    ]);
+    oracle_aggregator_client.set_asset_config(
+        &Asset::Stellar(asset_1.clone()),
+        &single_source_config(&e, &cex_oracle.address, &asset_1),
+    );
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    cex_oracle.set_price(
+        &Vec::from_array(&e, [1_97010000000000]),
+        &(recent_norm_time - 300),
+    );
+    usd_oracle.set_price(
+        &Vec::from_array(&e, [0_99500000000000]),
+        &(recent_norm_time - 300),
+    );
+    cex_oracle.set_price(&Vec::from_array(&e, [1_98000000000000]), &recent_norm_time);
+    usd_oracle.set_price(&Vec::from_array(&e, [0_99000000000000]), &recent_norm_time);
+
+    // prices in USD are divided by the price of USDC in USD for the same round
+    let price = oracle_aggregator_client
+        .lastprice(&Asset::Stellar(asset_1.clone()))
+        .unwrap();
+    assert_eq!(price.price, 2_0000000);
+    assert_eq!(price.timestamp, recent_norm_time);
+
+    let price = oracle_aggregator_client
+        .price(&Asset::Stellar(asset_1.clone()), &(recent_norm_time - 300))
+        .unwrap();
+    assert_eq!(price.price, 1_9800000);
+
+    let prices = oracle_aggregator_client
+        .prices(&Asset::Stellar(asset_1.clone()), &2)
+        .unwrap();
+    assert_eq!(prices.len(), 2);
+    assert_eq!(prices.get_unchecked(0).price, 2_0000000);
+    assert_eq!(prices.get_unchecked(1).price, 1_9800000);
+
+    // a source is dropped if the price of USDC in USD is missing for its round
+    cex_oracle.set_price(
+        &Vec::from_array(&e, [1_99000000000000]),
+        &(recent_norm_time + 300),
+    );
+    usd_oracle.set_price(&vec![&e], &(recent_norm_time + 300));
+    e.jump(60);
+    let price = oracle_aggregator_client.lastprice(&Asset::Stellar(asset_1));
+    assert!(price.is_none());
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #103)")]
+fn test_usd_source_without_usd_conversion() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+    let cex_oracle = create_usd_oracle(&e, &asset_1);
+
+    oracle_aggregator_client.set_oracles(&vec![&e, oracle_1.address.clone(), cex_oracle.address]);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #103)")]
+fn test_remove_usd_conversion_with_usd_source() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+    let cex_oracle = create_usd_oracle(&e, &asset_1);
+    oracle_aggregator_client.set_usd_conversion(&Some(UsdConversion::Fixed));
+    oracle_aggregator_client.set_oracles(&vec![&e, oracle_1.address.clone(), cex_oracle.address]);
+
+    oracle_aggregator_client.set_usd_conversion(&None);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #103)")]
+fn test_usd_conversion_source_not_in_usd() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+
+    // the oracle reports prices in USDC, not USD
+    oracle_aggregator_client.set_usd_conversion(&Some(UsdConversion::Source(AssetSource {
+        oracle_id: oracle_1.address.clone(),
+        asset: Asset::Stellar(usdc),
+    })));
+}
+
+#[test]
+#[should_panic(expected = "Error(Auth, InvalidAction)")]
+fn test_set_usd_conversion_requires_admin() {
+    let e = Env::default();
+    e.set_default_info();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, _) = setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+
+    e.set_auths(&[]);
+    oracle_aggregator_client.set_usd_conversion(&Some(UsdConversion::Fixed));
+}
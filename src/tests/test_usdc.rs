@@ -0,0 +1,210 @@
+#![cfg(test)]
+use crate::{
+    testutils::{assert_assets_equal, create_usd_oracle, setup_default_aggregator, EnvTestUtils},
+    types::AssetSource,
+};
+use sep_40_oracle::Asset;
+use soroban_sdk::{
+    testutils::{Address as _, AuthorizedFunction, AuthorizedInvocation},
+    vec, Address, Env, IntoVal, Symbol, Vec,
+};
+
+#[test]
+fn test_usdc_source() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+    let usd_oracle = create_usd_oracle(&e, &usdc);
+
+    let source = AssetSource {
+        oracle_id: usd_oracle.address.clone(),
+        asset: Asset::Stellar(usdc.clone()),
+    };
+    oracle_aggregator_client.set_usdc_source(&Some(source.clone()));
+    assert_eq!(
+        e.auths()[0],
+        (
+            oracle_aggregator_client.admin(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    oracle_aggregator_client.address.clone(),
+                    Symbol::new(&e, "set_usdc_source"),
+                    vec![&e, Some(source).into_val(&e)]
+                )),
+                sub_invocations: std::vec![]
+            }
+        )
+    );
+    let usdc_source = oracle_aggregator_client.usdc_source().unwrap();
+    assert_eq!(usdc_source.oracle.oracle_id, usd_oracle.address);
+    assert_eq!(usdc_source.oracle.decimals, 14);
+    assert!(assert_assets_equal(
+        oracle_aggregator_client.base(),
+        Asset::Other(Symbol::new(&e, "USD"))
+    ));
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_110000000, 2_000000000]),
+        &recent_norm_time,
+    );
+    usd_oracle.set_price(
+        &Vec::from_array(&e, [0_99000000000000]),
+        &(recent_norm_time - 300),
+    );
+    usd_oracle.set_price(&vec![&e], &recent_norm_time);
+
+    let price_usdc = oracle_aggregator_client
+        .lastprice(&Asset::Stellar(usdc.clone()))
+        .unwrap();
+    assert_eq!(price_usdc.price, 0_9900000);
+    assert_eq!(price_usdc.timestamp, recent_norm_time - 300);
+
+    // prices are converted to USD, using the older timestamp
+    let price_0 = oracle_aggregator_client
+        .lastprice(&Asset::Stellar(asset_0.clone()))
+        .unwrap();
+    assert_eq!(price_0.price, 0_1089000);
+    assert_eq!(price_0.timestamp, recent_norm_time - 300);
+
+    let sourced_price_1 = oracle_aggregator_client
+        .lastprice_with_source(&Asset::Stellar(asset_1.clone()))
+        .unwrap();
+    assert_eq!(sourced_price_1.price.price, 1_9800000);
+    assert_eq!(
+        sourced_price_1.sources,
+        vec![&e, oracle_1.address.clone(), usd_oracle.address.clone()]
+    );
+
+    // historical prices use the price of USDC at the same timestamp
+    let price_usdc = oracle_aggregator_client
+        .price(&Asset::Stellar(usdc.clone()), &(recent_norm_time - 300))
+        .unwrap();
+    assert_eq!(price_usdc.price, 0_9900000);
+    let price_0 =
+        oracle_aggregator_client.price(&Asset::Stellar(asset_0.clone()), &recent_norm_time);
+    assert!(price_0.is_none());
+
+    // unset the source to price USDC at 1 again
+    oracle_aggregator_client.set_usdc_source(&None);
+    assert!(oracle_aggregator_client.usdc_source().is_none());
+    assert!(assert_assets_equal(
+        oracle_aggregator_client.base(),
+        Asset::Stellar(usdc.clone())
+    ));
+    let price_usdc = oracle_aggregator_client
+        .lastprice(&Asset::Stellar(usdc))
+        .unwrap();
+    assert_eq!(price_usdc.price, 1_0000000);
+    let price_0 = oracle_aggregator_client
+        .lastprice(&Asset::Stellar(asset_0))
+        .unwrap();
+    assert_eq!(price_0.price, 0_1100000);
+}
+
+#[test]
+fn test_usdc_source_prices() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+    let usd_oracle = create_usd_oracle(&e, &usdc);
+    oracle_aggregator_client.set_usdc_source(&Some(AssetSource {
+        oracle_id: usd_oracle.address.clone(),
+        asset: Asset::Stellar(usdc.clone()),
+    }));
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_120000000, 1_010000000]),
+        &(recent_norm_time - 300),
+    );
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_110000000, 1_000000000]),
+        &recent_norm_time,
+    );
+    usd_oracle.set_price(
+        &Vec::from_array(&e, [0_98000000000000]),
+        &(recent_norm_time - 300),
+    );
+    usd_oracle.set_price(&Vec::from_array(&e, [1_00000000000000]), &recent_norm_time);
+
+    let prices_0 = oracle_aggregator_client
+        .prices(&Asset::Stellar(asset_0), &2)
+        .unwrap();
+    assert_eq!(prices_0.len(), 2);
+    assert_eq!(prices_0.get_unchecked(0).price, 0_1100000);
+    assert_eq!(prices_0.get_unchecked(1).price, 0_1176000);
+
+    let prices_usdc = oracle_aggregator_client
+        .prices(&Asset::Stellar(usdc), &2)
+        .unwrap();
+    assert_eq!(prices_usdc.len(), 2);
+    assert_eq!(prices_usdc.get_unchecked(0).price, 1_0000000);
+    assert_eq!(prices_usdc.get_unchecked(1).price, 0_9800000);
+}
+
+#[test]
+fn test_usdc_source_stale() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+    let usd_oracle = create_usd_oracle(&e, &usdc);
+    oracle_aggregator_client.set_usdc_source(&Some(AssetSource {
+        oracle_id: usd_oracle.address.clone(),
+        asset: Asset::Stellar(usdc.clone()),
+    }));
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_110000000, 1_000000000]),
+        &recent_norm_time,
+    );
+    usd_oracle.set_price(
+        &Vec::from_array(&e, [0_99000000000000]),
+        &(recent_norm_time - 1200),
+    );
+
+    // USDC can't be priced, so no asset can be priced in USD
+    let price_usdc = oracle_aggregator_client.lastprice(&Asset::Stellar(usdc));
+    assert!(price_usdc.is_none());
+    let price_0 = oracle_aggregator_client.lastprice(&Asset::Stellar(asset_0));
+    assert!(price_0.is_none());
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #103)")]
+fn test_usdc_source_not_in_usd() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+
+    // the oracle reports prices in USDC, not USD
+    oracle_aggregator_client.set_usdc_source(&Some(AssetSource {
+        oracle_id: oracle_1.address.clone(),
+        asset: Asset::Stellar(usdc),
+    }));
+}
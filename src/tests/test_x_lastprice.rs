@@ -0,0 +1,214 @@
+#![cfg(test)]
+use crate::{
+    testutils::{
+        create_asset_configs, create_mock_oracle, create_oracle_aggregator,
+        setup_default_aggregator, EnvTestUtils,
+    },
+    types::PriceBounds,
+};
+use sep_40_oracle::Asset;
+use soroban_sdk::{testutils::Address as _, vec, Address, Env, Vec};
+
+#[test]
+fn test_x_lastprice() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    // price asset 0 from oracle 0, and asset 1 from oracle 1
+    let oracle_0 = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &9, &300);
+    let oracle_1 = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &14, &300);
+    let mut assets = create_asset_configs(
+        &e,
+        &vec![&e, oracle_0.address.clone()],
+        &vec![&e, asset_0.clone()],
+    );
+    assets.append(&create_asset_configs(
+        &e,
+        &vec![&e, oracle_1.address.clone()],
+        &vec![&e, asset_1.clone()],
+    ));
+    let (_, oracle_aggregator_client) = create_oracle_aggregator(
+        &e,
+        &Address::generate(&e),
+        &vec![&e, oracle_0.address.clone(), oracle_1.address.clone()],
+        &usdc,
+        &7,
+        &900,
+        &assets,
+        &None,
+    );
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    oracle_0.set_price(&Vec::from_array(&e, [0_110000000, 0]), &recent_norm_time);
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0, 1_01000000000000]),
+        &(recent_norm_time - 300),
+    );
+
+    let x_price = oracle_aggregator_client
+        .x_lastprice(
+            &Asset::Stellar(asset_0.clone()),
+            &Asset::Stellar(asset_1.clone()),
+        )
+        .unwrap();
+    assert_eq!(x_price.price, 0_1089108);
+    assert_eq!(x_price.timestamp, recent_norm_time - 300);
+
+    let x_price = oracle_aggregator_client
+        .x_lastprice(&Asset::Stellar(asset_1), &Asset::Stellar(asset_0))
+        .unwrap();
+    assert_eq!(x_price.price, 9_1818181);
+    assert_eq!(x_price.timestamp, recent_norm_time - 300);
+}
+
+#[test]
+fn test_x_lastprice_usdc() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_125000000, 1_000000000]),
+        &recent_norm_time,
+    );
+
+    let x_price = oracle_aggregator_client
+        .x_lastprice(
+            &Asset::Stellar(asset_0.clone()),
+            &Asset::Stellar(usdc.clone()),
+        )
+        .unwrap();
+    assert_eq!(x_price.price, 0_1250000);
+    assert_eq!(x_price.timestamp, recent_norm_time);
+
+    let x_price = oracle_aggregator_client
+        .x_lastprice(&Asset::Stellar(usdc.clone()), &Asset::Stellar(asset_0))
+        .unwrap();
+    assert_eq!(x_price.price, 8_0000000);
+    assert_eq!(x_price.timestamp, recent_norm_time);
+
+    let x_price = oracle_aggregator_client
+        .x_lastprice(&Asset::Stellar(usdc.clone()), &Asset::Stellar(usdc))
+        .unwrap();
+    assert_eq!(x_price.price, 1_0000000);
+}
+
+#[test]
+fn test_x_lastprice_missing_price() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+
+    // neither asset has a recent price
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_125000000, 1_000000000]),
+        &recent_norm_time,
+    );
+    e.jump(200);
+    let x_price =
+        oracle_aggregator_client.x_lastprice(&Asset::Stellar(asset_1), &Asset::Stellar(asset_0));
+    assert!(x_price.is_none());
+}
+
+#[test]
+fn test_x_lastprice_large_decimals() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let oracle = create_mock_oracle(&e, &usdc, &asset_0, &asset_1, &18, &300);
+    let oracles = vec![&e, oracle.address.clone()];
+    let assets = create_asset_configs(&e, &oracles, &vec![&e, asset_0.clone(), asset_1.clone()]);
+    let (_, oracle_aggregator_client) = create_oracle_aggregator(
+        &e,
+        &Address::generate(&e),
+        &oracles,
+        &usdc,
+        &18,
+        &900,
+        &assets,
+        &None,
+    );
+
+    // 100B * 10^18 * 10^18 overflows an i128
+    oracle.set_price(
+        &Vec::from_array(&e, [100_000_000_000 * 10i128.pow(18), 2 * 10i128.pow(18)]),
+        &e.ledger().timestamp(),
+    );
+
+    let x_price = oracle_aggregator_client
+        .x_lastprice(&Asset::Stellar(asset_0), &Asset::Stellar(asset_1))
+        .unwrap();
+    assert_eq!(x_price.price, 50_000_000_000 * 10i128.pow(18));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #110)")]
+fn test_x_lastprice_out_of_bounds() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+    oracle_aggregator_client.set_price_bounds(
+        &Asset::Stellar(asset_0.clone()),
+        &Some(PriceBounds {
+            min: 0_2000000,
+            max: 0_5000000,
+        }),
+    );
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    oracle_1.set_price(
+        &Vec::from_array(&e, [0_110000000, 1_000000000]),
+        &recent_norm_time,
+    );
+
+    oracle_aggregator_client.x_lastprice(&Asset::Stellar(asset_1), &Asset::Stellar(asset_0));
+}
+
+#[test]
+fn test_x_lastprice_zero_price() {
+    let e = Env::default();
+    e.set_default_info();
+    e.mock_all_auths();
+    let usdc = Address::generate(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+
+    let (oracle_aggregator_client, oracle_1) =
+        setup_default_aggregator(&e, &usdc, &asset_0, &asset_1);
+
+    let recent_norm_time = e.ledger().timestamp() / 300 * 300;
+    // the quote asset has a price of zero, which is dropped like a missing price
+    oracle_1.set_price(&Vec::from_array(&e, [0_125000000, 0]), &recent_norm_time);
+
+    assert!(oracle_aggregator_client
+        .x_lastprice(&Asset::Stellar(asset_0), &Asset::Stellar(asset_1))
+        .is_none());
+}
@@ -1,14 +1,18 @@
 #![cfg(test)]
 
-use crate::contract::OracleAggregatorClient;
+use crate::{
+    contract::OracleAggregatorClient,
+    types::{AssetConfig, AssetSource, UsdConversion},
+};
 use sep_40_oracle::{
     testutils::{Asset as MockAsset, MockPriceOracleClient, MockPriceOracleWASM},
     Asset,
 };
 use soroban_sdk::{
     testutils::{Address as _, Ledger, LedgerInfo},
-    vec, Address, Env, Vec,
+    vec, Address, Env, Symbol, Vec,
 };
+#[allow(clippy::too_many_arguments)]
 pub mod oracle_aggregator {
     soroban_sdk::contractimport!(
         file = "./target/wasm32-unknown-unknown/optimized/oracle_aggregator.wasm"
@@ -57,58 +61,136 @@ impl EnvTestUtils for Env {
 }
 
 /// Deploy an oracle aggreator contract
+#[allow(clippy::too_many_arguments)]
 pub fn create_oracle_aggregator<'a>(
     e: &Env,
-    oracle: &Address,
+    admin: &Address,
+    oracles: &Vec<Address>,
     usdc: &Address,
     decimals: &u32,
     max_age: &u64,
+    assets: &Vec<(Asset, AssetConfig)>,
+    usd_conversion: &Option<UsdConversion>,
 ) -> (Address, OracleAggregatorClient<'a>) {
-    let oracle_aggregator_address = Address::generate(&e);
+    let oracle_aggregator_address = Address::generate(e);
     e.register_at(
         &oracle_aggregator_address,
         oracle_aggregator::WASM,
-        (oracle, usdc.clone(), decimals, max_age),
+        (
+            admin.clone(),
+            oracles.clone(),
+            usdc.clone(),
+            decimals,
+            max_age,
+            assets.clone(),
+            usd_conversion.clone(),
+        ),
     );
     let oracle_aggregator_client: OracleAggregatorClient<'a> =
-        OracleAggregatorClient::new(&e, &oracle_aggregator_address);
-    return (oracle_aggregator_address, oracle_aggregator_client);
+        OracleAggregatorClient::new(e, &oracle_aggregator_address);
+    (oracle_aggregator_address, oracle_aggregator_client)
 }
 
-/// Setup an oracle aggregator with default test setttings based on the current env timestamp.
-///
-/// ### Returns
-/// Two oracle aggegator clients:
-/// - (0 and 1 oracle, 2 oracle)
-pub fn setup_default_aggregator<'a>(
+/// Build asset configs that price each asset from every oracle, using the same asset identifier
+pub fn create_asset_configs(
+    e: &Env,
+    oracles: &Vec<Address>,
+    assets: &Vec<Address>,
+) -> Vec<(Asset, AssetConfig)> {
+    let mut asset_configs = Vec::new(e);
+    for asset in assets.iter() {
+        let mut sources = Vec::new(e);
+        for oracle_id in oracles.iter() {
+            sources.push_back(AssetSource {
+                oracle_id,
+                asset: Asset::Stellar(asset.clone()),
+            });
+        }
+        asset_configs.push_back((
+            Asset::Stellar(asset),
+            AssetConfig {
+                sources,
+                fallbacks: Vec::new(e),
+                max_age: None,
+            },
+        ));
+    }
+    asset_configs
+}
+
+/// Deploy a mock source oracle that reports prices for asset 0 and asset 1 in USDC
+pub fn create_mock_oracle<'a>(
     e: &Env,
     usdc: &Address,
     asset_0: &Address,
     asset_1: &Address,
-) -> (OracleAggregatorClient<'a>, MockPriceOracleClient<'a>) {
-    // setup oracle with XLM and USDC price
-    let oracle_id = Address::generate(&e);
+    decimals: &u32,
+    resolution: &u32,
+) -> MockPriceOracleClient<'a> {
+    let oracle_id = Address::generate(e);
     e.register_at(&oracle_id, MockPriceOracleWASM, ());
-    let oracle = MockPriceOracleClient::new(&e, &oracle_id);
+    let oracle = MockPriceOracleClient::new(e, &oracle_id);
     oracle.set_data(
-        &Address::generate(&e),
+        &Address::generate(e),
         &MockAsset::Stellar(usdc.clone()),
         &Vec::from_array(
-            &e,
+            e,
             [
                 MockAsset::Stellar(asset_0.clone()),
                 MockAsset::Stellar(asset_1.clone()),
             ],
         ),
-        &9,
+        decimals,
+        resolution,
+    );
+
+    oracle.set_price(&vec![e, 0i128, 0i128], &0);
+    oracle
+}
+
+/// Deploy a mock source oracle that reports the price of an asset in USD, like the Reflector CEX
+/// and FX oracles
+pub fn create_usd_oracle<'a>(e: &Env, asset: &Address) -> MockPriceOracleClient<'a> {
+    let oracle_id = Address::generate(e);
+    e.register_at(&oracle_id, MockPriceOracleWASM, ());
+    let oracle = MockPriceOracleClient::new(e, &oracle_id);
+    oracle.set_data(
+        &Address::generate(e),
+        &MockAsset::Other(Symbol::new(e, "USD")),
+        &vec![e, MockAsset::Stellar(asset.clone())],
+        &14,
         &300,
     );
+    oracle
+}
 
-    oracle.set_price(&vec![&e, 0i128, 0i128], &0);
+/// Setup an oracle aggregator with default test setttings based on the current env timestamp.
+///
+/// ### Returns
+/// The oracle aggregator client and the source oracle client
+pub fn setup_default_aggregator<'a>(
+    e: &Env,
+    usdc: &Address,
+    asset_0: &Address,
+    asset_1: &Address,
+) -> (OracleAggregatorClient<'a>, MockPriceOracleClient<'a>) {
+    // setup oracle with XLM and USDC price
+    let oracle = create_mock_oracle(e, usdc, asset_0, asset_1, &9, &300);
 
-    let (_, aggregator_client) = create_oracle_aggregator(e, &oracle_id, usdc, &7, &900);
+    let oracles = vec![e, oracle.address.clone()];
+    let assets = create_asset_configs(e, &oracles, &vec![e, asset_0.clone(), asset_1.clone()]);
+    let (_, aggregator_client) = create_oracle_aggregator(
+        e,
+        &Address::generate(e),
+        &oracles,
+        usdc,
+        &7,
+        &900,
+        &assets,
+        &None,
+    );
 
-    return (aggregator_client, oracle);
+    (aggregator_client, oracle)
 }
 
 pub fn assert_assets_equal(a: Asset, b: Asset) -> bool {
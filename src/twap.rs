@@ -0,0 +1,121 @@
+use sep_40_oracle::PriceData;
+use soroban_sdk::{panic_with_error, Env, Vec};
+
+use crate::errors::OracleAggregatorErrors;
+
+/// Calculate the time-weighted average price of the most recent rounds
+///
+/// Each round is weighted by the time until the next round, and the most recent round by one
+/// `resolution` period. The average uses the timestamp of the most recent round.
+///
+/// ### Arguments
+/// * `prices` - The most recent rounds, ordered from newest to oldest
+/// * `records` - The number of rounds to average
+/// * `max_age` - The maximum age of a round used in the average (in seconds)
+/// * `resolution` - The resolution of the rounds (in seconds)
+///
+/// ### Errors
+/// * `InsufficientRounds` - Fewer than `records` rounds are at most `max_age` old, or `records` is zero
+pub fn time_weighted_average(
+    e: &Env,
+    prices: &Vec<PriceData>,
+    records: u32,
+    max_age: u64,
+    resolution: u32,
+) -> PriceData {
+    let oldest_timestamp = e.ledger().timestamp() - max_age;
+    let mut weighted_sum: i128 = 0;
+    let mut total_weight: i128 = 0;
+    let mut rounds: u32 = 0;
+    let mut next_timestamp: Option<u64> = None;
+    for price in prices.iter() {
+        if rounds == records || price.timestamp < oldest_timestamp {
+            break;
+        }
+        let weight = match next_timestamp {
+            Some(next_timestamp) => next_timestamp - price.timestamp,
+            None => resolution as u64,
+        } as i128;
+        weighted_sum += price.price * weight;
+        total_weight += weight;
+        rounds += 1;
+        next_timestamp = Some(price.timestamp);
+    }
+    if records == 0 || rounds < records {
+        panic_with_error!(e, OracleAggregatorErrors::InsufficientRounds);
+    }
+    PriceData {
+        price: weighted_sum / total_weight,
+        timestamp: prices.get_unchecked(0).timestamp,
+    }
+}
+
+// @dev: `twap` is tested in integration tests in `test_twap.rs`
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testutils::EnvTestUtils;
+    use soroban_sdk::vec;
+
+    #[test]
+    fn test_time_weighted_average() {
+        let e = Env::default();
+        e.set_default_info();
+        let now = e.ledger().timestamp() / 300 * 300;
+        let price = |price: i128, timestamp: u64| PriceData { price, timestamp };
+
+        // consecutive rounds are weighted equally
+        let result = time_weighted_average(
+            &e,
+            &vec![
+                &e,
+                price(1_0000000, now),
+                price(1_2000000, now - 300),
+                price(1_4000000, now - 600),
+            ],
+            3,
+            900,
+            300,
+        );
+        assert_eq!(result.price, 1_2000000);
+        assert_eq!(result.timestamp, now);
+
+        // a round followed by a missing round is weighted by both periods
+        let result = time_weighted_average(
+            &e,
+            &vec![
+                &e,
+                price(1_0000000, now),
+                price(1_3000000, now - 600),
+                price(9_0000000, now - 900),
+            ],
+            2,
+            900,
+            300,
+        );
+        assert_eq!(result.price, 1_2000000);
+        assert_eq!(result.timestamp, now);
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #107)")]
+    fn test_time_weighted_average_rounds_outside_window() {
+        let e = Env::default();
+        e.set_default_info();
+        let now = e.ledger().timestamp() / 300 * 300;
+        let price = |price: i128, timestamp: u64| PriceData { price, timestamp };
+
+        time_weighted_average(
+            &e,
+            &vec![
+                &e,
+                price(1_0000000, now - 600),
+                price(1_2000000, now - 900),
+                price(1_4000000, now - 1200),
+            ],
+            3,
+            900,
+            300,
+        );
+    }
+}
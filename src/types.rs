@@ -1,5 +1,5 @@
-use sep_40_oracle::Asset;
-use soroban_sdk::{contracttype, Address};
+use sep_40_oracle::{Asset, PriceData};
+use soroban_sdk::{contracttype, Address, String, Vec};
 
 #[contracttype]
 #[derive(Clone)]
@@ -13,3 +13,181 @@ pub struct OracleConfig {
     /// The base asset of the source oracle
     pub base: Asset,
 }
+
+#[contracttype]
+#[derive(Clone)]
+pub struct AssetSource {
+    /// The address of the source oracle
+    pub oracle_id: Address,
+    /// The asset identifier used by the source oracle
+    pub asset: Asset,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct AssetConfig {
+    /// The source oracles that report a price for the asset
+    pub sources: Vec<AssetSource>,
+    /// The fallback oracles used, in order, if no source oracle has a recent price
+    pub fallbacks: Vec<AssetSource>,
+    /// The maximum age of a price for the asset (in seconds). Uses the global max age if None.
+    pub max_age: Option<u64>,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct UsdcSource {
+    /// The config of the oracle that reports the price of USDC in USD
+    pub oracle: OracleConfig,
+    /// The asset identifier the oracle uses for USDC
+    pub asset: Asset,
+}
+
+/// How prices reported in USD by a source oracle are converted to USDC
+#[contracttype]
+#[derive(Clone)]
+pub enum UsdConversion {
+    /// 1 USD is converted to 1 USDC
+    Fixed,
+    /// USD is converted to USDC with the price of USDC in USD reported by an oracle, and the asset
+    /// identifier it uses for USDC
+    Source(AssetSource),
+}
+
+/// The config of a `UsdConversion`, with the config of the oracle loaded from the oracle itself
+#[contracttype]
+#[derive(Clone)]
+pub enum UsdConversionConfig {
+    /// 1 USD is converted to 1 USDC
+    Fixed,
+    /// USD is converted to USDC with the price of USDC in USD reported by an oracle
+    Source(UsdcSource),
+}
+
+/// The rounding applied when a price loses precision
+#[contracttype]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RoundingMode {
+    /// Round toward negative infinity
+    Floor,
+    /// Round toward positive infinity
+    Ceil,
+    /// Round to the nearest value, and ties to the even value
+    HalfEven,
+}
+
+/// How a source oracle is queried for an older round when `lastprice` does not return a price
+#[contracttype]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RetryStrategy {
+    /// Call `price` once per `resolution` step back, up to `max_age`
+    Price,
+    /// Fetch the rounds within `max_age` with a single `prices` call, and use the newest round
+    Prices,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct PriceBounds {
+    /// The minimum valid price, in the aggregator's decimals
+    pub min: i128,
+    /// The maximum valid price, in the aggregator's decimals
+    pub max: i128,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct DerivedAsset {
+    /// The asset the price is derived from, which must have an asset config
+    pub underlying: Asset,
+    /// The ratio applied to the price of the underlying asset, in basis points
+    pub ratio: u32,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct PathLeg {
+    /// The config of the oracle that reports the leg
+    pub oracle: OracleConfig,
+    /// The asset identifier the oracle uses for the asset it reports
+    pub asset: Asset,
+}
+
+/// The live feed the peg of a pegged asset is checked against
+#[contracttype]
+#[derive(Clone)]
+pub enum PegFeed {
+    /// The peg is not checked, and the fixed price is always reported
+    Unchecked,
+    /// The peg is checked against the price reported by a source oracle
+    Source(AssetSource),
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct PeggedAsset {
+    /// The fixed price of the asset in USDC, in the aggregator's decimals
+    pub price: i128,
+    /// The live feed the peg is checked against
+    pub feed: PegFeed,
+    /// The max deviation of the price reported by the feed from the fixed price, in basis points
+    pub tolerance: u32,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct GracePrice {
+    /// The price of the asset
+    pub price: PriceData,
+    /// If the price is the last known good price, served from the cache after it exceeded `max_age`
+    pub stale: bool,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct SourcedPrice {
+    /// The price of the asset
+    pub price: PriceData,
+    /// The oracles that reported the price
+    pub sources: Vec<Address>,
+    /// The index of the fallback oracle that reported the price, or None if the source oracles did
+    pub fallback: Option<u32>,
+}
+
+/// The method of the source oracle that answered for a price
+#[contracttype]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PriceMethod {
+    /// The price was returned by `lastprice`
+    LastPrice,
+    /// `lastprice` did not return a price, and an older round was found with `price`
+    Price,
+    /// `lastprice` did not return a price, and an older round was found with `prices`
+    Prices,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct PriceDetail {
+    /// The price of the asset
+    pub price: PriceData,
+    /// The source oracle that answered for the price, or None if the asset is priced at a fixed price
+    pub oracle_id: Option<Address>,
+    /// The method of the source oracle that answered for the price
+    pub method: PriceMethod,
+    /// The number of `resolution` steps back the answer came from
+    pub steps: u32,
+    /// The age of the price when it was fetched (in seconds)
+    pub age: u64,
+    /// The number of source oracles whose prices were aggregated into the price
+    pub agreed: u32,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct Version {
+    /// The version of the contract
+    pub contract: String,
+    /// The version of the storage schema
+    pub schema: u32,
+}
@@ -0,0 +1,36 @@
+use soroban_sdk::{panic_with_error, Env};
+
+use crate::{errors::OracleAggregatorErrors, storage};
+
+/// The current version of the storage schema
+///
+/// Increment this and add a migration step in `migrate` whenever the storage layout changes.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Migrate the storage from the stored schema version to `SCHEMA_VERSION`
+///
+/// No released version has changed the storage layout yet, so there are no migration steps.
+/// A future step for schema version `n` runs when the stored schema version is below `n`.
+///
+/// ### Errors
+/// * `InvalidSchemaVersion` - The stored schema version is newer than `SCHEMA_VERSION`
+pub fn migrate(e: &Env) {
+    let schema_version = storage::get_schema_version(e);
+    if schema_version > SCHEMA_VERSION {
+        panic_with_error!(e, OracleAggregatorErrors::InvalidSchemaVersion);
+    }
+    storage::set_schema_version(e, &SCHEMA_VERSION);
+}
+
+/// Require the storage to be on the schema version of the current WASM
+///
+/// Between an `upgrade` to a WASM with a new schema and the `migrate` call, the new code
+/// would read the old storage layout, so prices are not served until the storage is migrated.
+///
+/// ### Errors
+/// * `InvalidSchemaVersion` - The stored schema version is not `SCHEMA_VERSION`
+pub fn require_current_schema(e: &Env) {
+    if storage::get_schema_version(e) != SCHEMA_VERSION {
+        panic_with_error!(e, OracleAggregatorErrors::InvalidSchemaVersion);
+    }
+}
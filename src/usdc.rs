@@ -0,0 +1,177 @@
+use sep_40_oracle::{PriceData, PriceFeedClient};
+use soroban_sdk::{panic_with_error, Env, Vec};
+
+use crate::{
+    config,
+    fixed_point::{mul_div, scalar},
+    price_data::{get_source_price, normalize_source_price, RoundReader, MAX_RECORDS},
+    storage,
+    types::{OracleConfig, RoundingMode, UsdConversionConfig, UsdcSource},
+};
+
+/// Fetch the most recent price of USDC, rounded with `rounding` if it loses precision
+///
+/// If a USDC source is configured, USDC is priced in USD by the USDC source, and None is returned
+/// if it does not have a price at most `max_age` old. Otherwise, USDC is priced at 1.
+pub fn get_usdc_price(e: &Env, rounding: RoundingMode) -> Option<PriceData> {
+    match storage::get_usdc_source(e) {
+        Some(source) => {
+            let oldest_timestamp = e.ledger().timestamp() - storage::get_max_age(e);
+            get_source_price(e, &source.oracle, &source.asset, oldest_timestamp)
+                .and_then(|price| normalize_usdc_price(e, &source, price, rounding))
+        }
+        None => Some(fixed_usdc_price(e, e.ledger().timestamp())),
+    }
+}
+
+/// Fetch the price of USDC at a specific timestamp, read with `reader`
+///
+/// If a USDC source is configured, returns None if it did not report a price for the round
+/// containing the timestamp. Otherwise, USDC is priced at 1.
+pub fn get_usdc_price_at(e: &Env, reader: &mut RoundReader, timestamp: u64) -> Option<PriceData> {
+    match storage::get_usdc_source(e) {
+        Some(source) => reader
+            .price(e, &source.oracle, &source.asset, timestamp)
+            .and_then(|price| normalize_usdc_price(e, &source, price, storage::get_rounding(e))),
+        None => Some(fixed_usdc_price(e, timestamp)),
+    }
+}
+
+/// Fetch up to `records` of the most recent prices of USDC
+///
+/// If a USDC source is configured, rounds it did not report a price for are skipped. Otherwise,
+/// USDC is priced at 1 for each `resolution` step back from the most recent round. At most
+/// `MAX_RECORDS` rounds are fetched.
+pub fn get_usdc_prices(e: &Env, records: u32, resolution: u32) -> Option<Vec<PriceData>> {
+    let records = records.min(MAX_RECORDS);
+    match storage::get_usdc_source(e) {
+        Some(source) => {
+            let oracle = PriceFeedClient::new(e, &source.oracle.oracle_id);
+            let prices = oracle.prices(&source.asset, &records)?;
+            let rounding = storage::get_rounding(e);
+            let mut normalized_prices = Vec::new(e);
+            for price in prices.iter() {
+                if let Some(price) = normalize_usdc_price(e, &source, price, rounding) {
+                    normalized_prices.push_back(price);
+                }
+            }
+            if normalized_prices.is_empty() {
+                return None;
+            }
+            Some(normalized_prices)
+        }
+        None => {
+            let resolution = resolution as u64;
+            let mut timestamp = e.ledger().timestamp() / resolution * resolution;
+            let mut prices = Vec::new(e);
+            for _ in 0..records {
+                prices.push_back(fixed_usdc_price(e, timestamp));
+                timestamp = match timestamp.checked_sub(resolution) {
+                    Some(timestamp) => timestamp,
+                    None => break,
+                };
+            }
+            Some(prices)
+        }
+    }
+}
+
+/// Convert a price in USDC to a price in USD using the price of USDC in USD
+///
+/// The converted price uses the older of the two timestamps, and is rounded with `rounding`.
+///
+/// ### Panics
+/// * `ScalingOverflow` - The converted price does not fit in an i128
+pub fn usdc_to_usd(
+    e: &Env,
+    price: &PriceData,
+    usdc_price: &PriceData,
+    rounding: RoundingMode,
+) -> PriceData {
+    let converted_price = scalar(storage::get_decimals(e))
+        .and_then(|scalar| mul_div(e, price.price, usdc_price.price, scalar, rounding))
+        .unwrap_or_else(|error| panic_with_error!(e, error));
+    PriceData {
+        price: converted_price,
+        timestamp: price.timestamp.min(usdc_price.timestamp),
+    }
+}
+
+/// Convert a price reported by a source oracle in USD to USDC, in the decimals of the source oracle
+///
+/// Prices from source oracles that report in USDC are returned as is. If the USD conversion uses
+/// an oracle, the price is divided by the price of USDC in USD for the round containing the
+/// timestamp of the price, and uses the older of the two timestamps. Returns None if the oracle
+/// did not report a positive price of USDC for the round. The converted price is rounded with
+/// `rounding`.
+///
+/// ### Panics
+/// * `ScalingOverflow` - The converted price does not fit in an i128
+pub fn usd_to_usdc(
+    e: &Env,
+    config: &OracleConfig,
+    price: PriceData,
+    rounding: RoundingMode,
+) -> Option<PriceData> {
+    read_usd_to_usdc(e, &mut RoundReader::new(e), config, price, rounding)
+}
+
+/// Convert a price reported by a source oracle in USD to USDC like `usd_to_usdc`, reading the
+/// price of USDC in USD with `reader`
+///
+/// ### Panics
+/// * `ScalingOverflow` - The converted price does not fit in an i128
+pub fn read_usd_to_usdc(
+    e: &Env,
+    reader: &mut RoundReader,
+    config: &OracleConfig,
+    price: PriceData,
+    rounding: RoundingMode,
+) -> Option<PriceData> {
+    if !config::is_usd(e, &config.base) {
+        return Some(price);
+    }
+    match storage::get_usd_conversion(e) {
+        Some(UsdConversionConfig::Source(source)) => {
+            let usdc_price = reader.price(e, &source.oracle, &source.asset, price.timestamp)?;
+            if usdc_price.price <= 0 {
+                return None;
+            }
+            let converted_price = scalar(source.oracle.decimals)
+                .and_then(|scalar| mul_div(e, price.price, scalar, usdc_price.price, rounding))
+                .unwrap_or_else(|error| panic_with_error!(e, error));
+            Some(PriceData {
+                price: converted_price,
+                timestamp: price.timestamp.min(usdc_price.timestamp),
+            })
+        }
+        // 1 USD is converted to 1 USDC
+        _ => Some(price),
+    }
+}
+
+/// The fixed price of USDC (1 with the aggregator's decimals) at the given timestamp
+pub fn fixed_usdc_price(e: &Env, timestamp: u64) -> PriceData {
+    PriceData {
+        price: scalar(storage::get_decimals(e)).unwrap_or_else(|error| panic_with_error!(e, error)),
+        timestamp,
+    }
+}
+
+/// Normalize a price reported by the USDC source to the aggregator's decimals
+///
+/// Returns None if the normalized price is not positive, or does not fit in an i128, like a
+/// missing price.
+fn normalize_usdc_price(
+    e: &Env,
+    source: &UsdcSource,
+    price: PriceData,
+    rounding: RoundingMode,
+) -> Option<PriceData> {
+    normalize_source_price(
+        price,
+        &storage::get_decimals(e),
+        &source.oracle.decimals,
+        rounding,
+    )
+}